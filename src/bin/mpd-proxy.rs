@@ -0,0 +1,83 @@
+//! Shares one connection to the server configured by `MPD_HOST` and `MPD_PORT` between all
+//! clients connecting to the address given as argument, `localhost:6601` by default, or the Unix
+//! socket at it if it contains a `/`.
+
+use serde_mpd::{
+    client::{AsyncClient, ConnectionTarget, SessionConfig, Stream},
+    proxy::Proxy,
+};
+use std::{env, process::ExitCode};
+
+async fn connect(target: &ConnectionTarget) -> serde_mpd::Result<AsyncClient> {
+    // The password is sent before the connection is kept in `idle`, which may require it.
+    let config = SessionConfig {
+        password: target.password.clone(),
+        ..SessionConfig::default()
+    };
+    match target.connect()? {
+        Stream::Tcp(stream) => {
+            stream.set_nonblocking(true)?;
+            AsyncClient::with_session(tokio::net::TcpStream::from_std(stream)?, &config).await
+        }
+        #[cfg(unix)]
+        Stream::Unix(stream) => {
+            stream.set_nonblocking(true)?;
+            AsyncClient::with_session(tokio::net::UnixStream::from_std(stream)?, &config).await
+        }
+    }
+}
+
+async fn run(listen: &str) -> serde_mpd::Result<()> {
+    let target = ConnectionTarget::from_env()?;
+    let proxy = Proxy::new(connect(&target).await?);
+
+    #[cfg(unix)]
+    if listen.contains('/') {
+        let listener = tokio::net::UnixListener::bind(listen)?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            spawn(&proxy, stream);
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        spawn(&proxy, stream);
+    }
+}
+
+fn spawn<S>(proxy: &Proxy, stream: S)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    let proxy = proxy.clone();
+    tokio::spawn(async move {
+        if let Err(error) = proxy.serve(stream).await {
+            eprintln!("mpd-proxy: {error}");
+        }
+    });
+}
+
+fn main() -> ExitCode {
+    let listen = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "localhost:6601".to_string());
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            eprintln!("mpd-proxy: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(run(&listen)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("mpd-proxy: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
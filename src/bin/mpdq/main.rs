@@ -0,0 +1,245 @@
+//! Sends a command to the server configured by `MPD_HOST` and `MPD_PORT` and prints the response
+//! as JSON, or reads responses captured in a file instead.
+//!
+//! Responses are transcoded through the self-describing deserializer. The responses of the
+//! commands in [`LIST_COMMANDS`] are always printed as an array of objects, even if they hold a
+//! single record or none at all. Other responses are printed as an array if their first key
+//! repeats and as a single object otherwise. Keys occurring repeatedly in a record become arrays
+//! and binary data is base64 encoded. With `--ndjson` every record is printed as an object on a
+//! line of its own.
+//!
+//! When reading a file, the command whose responses were captured may be given to pick their
+//! shape.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{
+    de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+use serde_json::{Map, Value as Json};
+use serde_mpd::{
+    client::{Client, ConnectionTarget},
+    read::Slice,
+    CommandSerializer, RequestDeserializer,
+};
+use std::{
+    env, fmt, fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+#[cfg(test)]
+mod tests;
+
+const USAGE: &str = "usage: mpdq [--ndjson] [--file PATH] [COMMAND [ARGUMENT]...]";
+
+/// Commands that respond with a list of records.
+const LIST_COMMANDS: &[&str] = &[
+    "channels",
+    "commands",
+    "decoders",
+    "find",
+    "list",
+    "listall",
+    "listallinfo",
+    "listfiles",
+    "listmounts",
+    "listneighbors",
+    "listpartitions",
+    "listplaylist",
+    "listplaylistinfo",
+    "listplaylists",
+    "lsinfo",
+    "notcommands",
+    "outputs",
+    "playlistfind",
+    "playlistid",
+    "playlistinfo",
+    "playlistsearch",
+    "plchanges",
+    "plchangesposid",
+    "readmessages",
+    "search",
+    "tagtypes",
+    "urlhandlers",
+];
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    ndjson: bool,
+    /// File to read captured responses from, `-` for stdin.
+    file: Option<String>,
+    command: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Option<Args> {
+        let mut parsed = Args {
+            ndjson: false,
+            file: None,
+            command: Vec::new(),
+        };
+        let mut args = args.peekable();
+        while let Some(arg) = args.next_if(|arg| arg.starts_with("--")) {
+            match arg.as_str() {
+                "--ndjson" => parsed.ndjson = true,
+                "--file" => parsed.file = Some(args.next()?),
+                "--" => break,
+                _ => return None,
+            }
+        }
+        parsed.command.extend(args);
+        (parsed.file.is_some() || !parsed.command.is_empty()).then_some(parsed)
+    }
+
+    /// Whether the command responds with a list of records.
+    fn is_list(&self) -> bool {
+        self.command
+            .first()
+            .is_some_and(|name| LIST_COMMANDS.contains(&name.as_str()))
+    }
+}
+
+/// Writes `command` as a line, quoting and escaping its arguments where necessary.
+fn command_line(command: &[String]) -> serde_mpd::Result<Vec<u8>> {
+    let (name, arguments) = command.split_first().expect("expected a command");
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    ser.command(name, arguments)?;
+    Ok(ser.into_inner())
+}
+
+/// Transcodes whatever the deserializer describes into JSON.
+///
+/// Binary data is base64 encoded, as is the value of a `binary` key that happens to be valid
+/// UTF-8.
+#[derive(Clone, Copy)]
+struct Transcode {
+    binary: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Transcode {
+    type Value = Json;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Json, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Transcode {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a response")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Json, E> {
+        if self.binary {
+            return self.visit_bytes(v.as_bytes());
+        }
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Json, E> {
+        Ok(Json::String(STANDARD.encode(v)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(self)? {
+            values.push(value);
+        }
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(Transcode {
+                binary: key == "binary",
+            })?;
+            match object.get_mut(&key) {
+                Some(Json::Array(values)) => values.push(value),
+                Some(previous) => *previous = Json::Array(vec![previous.take(), value]),
+                None => {
+                    object.insert(key, value);
+                }
+            }
+        }
+        Ok(Json::Object(object))
+    }
+}
+
+fn print(json: &Json, ndjson: bool, out: &mut impl Write) -> io::Result<()> {
+    if !ndjson {
+        serde_json::to_writer_pretty(&mut *out, json)?;
+        return writeln!(out);
+    }
+
+    let records = match json {
+        Json::Array(records) => records.as_slice(),
+        record => std::slice::from_ref(record),
+    };
+    for record in records {
+        serde_json::to_writer(&mut *out, record)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Prints every response in `data`, which may start with the greeting of the server.
+///
+/// If `list` is set, the responses are lists of records.
+fn print_all(
+    mut data: &[u8],
+    list: bool,
+    ndjson: bool,
+    out: &mut impl Write,
+) -> serde_mpd::Result<()> {
+    if data.starts_with(b"OK MPD ") {
+        let end = data.iter().position(|byte| *byte == b'\n');
+        data = &data[end.map_or(data.len(), |end| end + 1)..];
+    }
+
+    let seed = Transcode { binary: false };
+    while !data.is_empty() {
+        let mut de = RequestDeserializer::from_read(Slice { src: data });
+        let json = if list {
+            de.deserialize_seq(seed)?
+        } else {
+            de.deserialize_any(seed)?
+        };
+        print(&json, ndjson, out)?;
+        data = de.into_inner().src;
+    }
+    Ok(())
+}
+
+fn run(args: &Args) -> serde_mpd::Result<()> {
+    let data = match args.file.as_deref() {
+        Some("-") => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            data
+        }
+        Some(path) => fs::read(path)?,
+        None => {
+            let mut client = Client::connect(&ConnectionTarget::from_env()?)?;
+            client.send_raw(&command_line(&args.command)?)?.data
+        }
+    };
+    print_all(&data, args.is_list(), args.ndjson, &mut io::stdout().lock())
+}
+
+fn main() -> ExitCode {
+    let Some(args) = Args::parse(env::args().skip(1)) else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("mpdq: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use super::{command_line, print_all, Args};
+use serde_json::json;
+
+fn args(args: &[&str]) -> Option<Args> {
+    Args::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+fn output(data: &[u8], list: bool, ndjson: bool) -> String {
+    let mut out = Vec::new();
+    print_all(data, list, ndjson, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn json(data: &[u8], list: bool) -> serde_json::Value {
+    serde_json::from_str(&output(data, list, false)).unwrap()
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        args(&["--ndjson", "find", "--file"]),
+        Some(Args {
+            ndjson: true,
+            file: None,
+            command: vec!["find".to_string(), "--file".to_string()],
+        })
+    );
+    assert_eq!(
+        args(&["--file", "-", "--", "--ndjson"]),
+        Some(Args {
+            ndjson: false,
+            file: Some("-".to_string()),
+            command: vec!["--ndjson".to_string()],
+        })
+    );
+    assert!(args(&["--file", "-"]).is_some());
+    assert!(args(&["playlistinfo"]).unwrap().is_list());
+    assert!(!args(&["status"]).unwrap().is_list());
+    assert_eq!(args(&[]), None);
+    assert_eq!(args(&["--ndjson"]), None);
+    assert_eq!(args(&["--file"]), None);
+    assert_eq!(args(&["--verbose", "status"]), None);
+}
+
+#[test]
+fn quoting() {
+    let command = ["find", "(Artist == \"A B\")", "sort", "Title"].map(String::from);
+    assert_eq!(
+        command_line(&command).unwrap(),
+        b"find \"(Artist == \\\"A B\\\")\" sort Title\n"
+    );
+    assert_eq!(command_line(&["status".to_string()]).unwrap(), b"status\n");
+    assert!(command_line(&["play".to_string(), "1\n".to_string()]).is_err());
+    assert!(command_line(&["play 1".to_string()]).is_err());
+}
+
+#[test]
+fn record() {
+    assert_eq!(
+        json(
+            b"file: a.flac\nArtist: A\nArtist: B\nArtist: C\nOK\n",
+            false
+        ),
+        json!({"file": "a.flac", "Artist": ["A", "B", "C"]})
+    );
+    assert_eq!(
+        json(b"size: 3\nbinary: 3\n\xff\x00a\nOK\n", false),
+        json!({"size": "3", "binary": "/wBh"})
+    );
+    assert_eq!(
+        json(b"binary: 2\nab\nOK\n", false),
+        json!({"binary": "YWI="})
+    );
+}
+
+#[test]
+fn shape() {
+    let song = b"file: a.flac\nTitle: A\nOK\n";
+    assert_eq!(json(song, false), json!({"file": "a.flac", "Title": "A"}));
+    assert_eq!(json(song, true), json!([{"file": "a.flac", "Title": "A"}]));
+    assert_eq!(json(b"OK\n", false), json!({}));
+    assert_eq!(json(b"OK\n", true), json!([]));
+    assert_eq!(
+        json(b"file: a.flac\nfile: b.flac\nOK\n", false),
+        json!([{"file": "a.flac"}, {"file": "b.flac"}])
+    );
+}
+
+#[test]
+fn print() {
+    let data = b"OK MPD 0.23.5\nstate: play\nOK\nfile: a.flac\nfile: b.flac\nOK\n";
+    assert_eq!(
+        output(data, false, false),
+        "{\n  \"state\": \"play\"\n}\n[\n  {\n    \"file\": \"a.flac\"\n  },\n  {\n    \"file\": \
+         \"b.flac\"\n  }\n]\n"
+    );
+    assert_eq!(
+        output(data, false, true),
+        "{\"state\":\"play\"}\n{\"file\":\"a.flac\"}\n{\"file\":\"b.flac\"}\n"
+    );
+    assert_eq!(
+        output(b"file: a.flac\nOK\nOK\n", true, true),
+        "{\"file\":\"a.flac\"}\n"
+    );
+    assert!(print_all(
+        b"ACK [50@0] {find} No such file\n",
+        true,
+        false,
+        &mut Vec::new()
+    )
+    .is_err());
+}
@@ -0,0 +1,232 @@
+//! A blocking client and, with the `tokio` feature, an asynchronous one.
+
+use crate::{
+    filter::Filter,
+    model::{Request, Song, Status, TagTypes},
+    protocol::{Command, Protocol, Response},
+    read::Slice,
+    Error, RequestDeserializer, Tag, Version,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+
+#[cfg(feature = "tokio")]
+pub use self::async_client::{AsyncClient, AsyncIdleWatcher};
+pub use self::{
+    idle::{Canceler, IdleWatcher},
+    session::SessionConfig,
+    target::{Address, ConnectionTarget, Stream, DEFAULT_HOST, DEFAULT_PORT},
+};
+
+#[cfg(feature = "tokio")]
+mod async_client;
+mod idle;
+mod session;
+
+mod target;
+
+#[cfg(all(test, unix))]
+mod tests;
+
+/// A blocking client, sending one command at a time over a stream like
+/// [`TcpStream`](std::net::TcpStream) or [`UnixStream`](std::os::unix::net::UnixStream).
+#[derive(Debug)]
+pub struct Client<S> {
+    stream: S,
+    protocol: Protocol,
+    version: Version,
+    /// Tags enabled by [`SessionConfig::tags`].
+    tags: Option<Vec<Tag>>,
+}
+
+impl Client<Stream> {
+    /// Connects to `target` and sends its password, if any.
+    pub fn connect(target: &ConnectionTarget) -> crate::Result<Self> {
+        let config = SessionConfig {
+            password: target.password.clone(),
+            ..SessionConfig::default()
+        };
+        Client::with_session(target.connect()?, &config)
+    }
+}
+
+impl<S: io::Read + io::Write> Client<S> {
+    /// Reads the greeting of the server from `stream`.
+    pub fn new(stream: S) -> crate::Result<Self> {
+        let mut client = Client {
+            stream,
+            protocol: Protocol::new(),
+            version: Version::default(),
+            tags: None,
+        };
+        while client.protocol.version().is_none() {
+            client.receive()?;
+            client.protocol.poll()?;
+        }
+        client.version = client.protocol.version().unwrap_or_default();
+        Ok(client)
+    }
+
+    /// Reads the greeting of the server from `stream` and sets up the session.
+    ///
+    /// A wrong password fails with an [`Ack`](crate::Ack) of kind
+    /// [`AckKind::Password`](crate::AckKind::Password), missing permissions for the other
+    /// commands with one of kind [`AckKind::Permission`](crate::AckKind::Permission).
+    pub fn with_session(stream: S, config: &SessionConfig) -> crate::Result<Self> {
+        let mut client = Client::new(stream)?;
+        if let Some(password) = &config.password {
+            client.command::<_, ()>(&Request::Password(password))?;
+        }
+        if let Some(tags) = &config.tags {
+            if client.version >= SessionConfig::TAGTYPES_CLEAR {
+                client.command::<_, ()>(&Request::Tagtypes("clear", &[]))?;
+                if !tags.is_empty() {
+                    client.command::<_, ()>(&Request::Tagtypes("enable", tags))?;
+                }
+                let enabled: TagTypes = client.command(&Request::TagtypesList)?;
+                client.tags = Some(enabled.tagtype);
+            }
+        }
+        if let Some(limit) = config.binary_limit {
+            if client.version >= SessionConfig::BINARYLIMIT {
+                client.command::<_, ()>(&Request::Binarylimit(limit))?;
+            }
+        }
+        Ok(client)
+    }
+
+    /// Returns the tags that are enabled, if they were restricted by [`SessionConfig::tags`].
+    ///
+    /// Songs returned by [`Client::current_song`], [`Client::queue`] and [`Client::find`] only
+    /// contain these tags.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.tags.as_deref()
+    }
+
+    /// Returns the protocol version the server announced in its greeting.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Sends `command`, serialized with [`CommandSerializer`](crate::CommandSerializer), and
+    /// deserializes its response as `T`.
+    pub fn command<C, T>(&mut self, command: &C) -> crate::Result<T>
+    where
+        C: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send(command)?;
+        self.response()
+    }
+
+    /// Sends already serialized command lines, terminated by a newline, and returns the raw
+    /// response, which may be an `ACK`.
+    ///
+    /// The lines have to make up exactly one command or command list, otherwise they are rejected.
+    pub fn send_raw(&mut self, lines: &[u8]) -> crate::Result<Response> {
+        check_complete(lines)?;
+        self.protocol.send_raw(lines)?;
+        self.stream.write_all(&self.protocol.take_outgoing())?;
+        self.raw_response()
+    }
+
+    pub fn status(&mut self) -> crate::Result<Status> {
+        self.command(&Request::Status)
+    }
+
+    /// Returns the current song, or `None` if there is none.
+    pub fn current_song(&mut self) -> crate::Result<Option<Song>> {
+        self.songs(&Request::Currentsong)
+    }
+
+    /// Starts playing the song at `pos` in the queue, or the current song.
+    pub fn play(&mut self, pos: Option<u32>) -> crate::Result<()> {
+        self.command(&Request::Play(pos))
+    }
+
+    /// Returns the songs in the queue.
+    pub fn queue(&mut self) -> crate::Result<Vec<Song>> {
+        self.songs(&Request::Playlistinfo)
+    }
+
+    /// Returns the songs in the database matching `filter`.
+    pub fn find(&mut self, filter: &Filter) -> crate::Result<Vec<Song>> {
+        self.songs(&Request::Find(filter))
+    }
+
+    /// Sends `request` and deserializes its response as `T`, skipping tags that aren't enabled.
+    fn songs<T: DeserializeOwned>(&mut self, request: &Request) -> crate::Result<T> {
+        self.send(request)?;
+        let response = self.raw_response()?;
+        T::deserialize(&mut self.deserializer(&response).tags(self.tags.clone()))
+    }
+
+    /// Writes `command` without waiting for its response.
+    fn send<C: Serialize + ?Sized>(&mut self, command: &C) -> crate::Result<()> {
+        self.protocol.send(command)?;
+        self.stream.write_all(&self.protocol.take_outgoing())?;
+        Ok(())
+    }
+
+    /// Reads the response to the oldest command in flight and deserializes it as `T`.
+    fn response<T: DeserializeOwned>(&mut self) -> crate::Result<T> {
+        let response = self.raw_response()?;
+        T::deserialize(&mut self.deserializer(&response))
+    }
+
+    fn raw_response(&mut self) -> crate::Result<Response> {
+        loop {
+            match self.protocol.poll()? {
+                Some(response) => return Ok(response),
+                None => self.receive()?,
+            }
+        }
+    }
+
+    fn deserializer<'de>(&self, response: &'de Response) -> RequestDeserializer<'de, Slice<'de>> {
+        RequestDeserializer::from_read(Slice {
+            src: &response.data,
+        })
+        .version(Some(self.version))
+    }
+
+    /// Hands the next chunk read from the stream to the protocol.
+    fn receive(&mut self) -> crate::Result<()> {
+        let mut buffer = [0; 4096];
+        let len = loop {
+            match self.stream.read(&mut buffer) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                result => break result?,
+            }
+        };
+        if len == 0 {
+            return Err(Error::Eof);
+        }
+        self.protocol.receive(&buffer[..len]);
+        Ok(())
+    }
+}
+
+/// Checks that `lines` make up exactly one complete command or command list, since a single
+/// response is read for them, and returns it.
+fn check_complete(lines: &[u8]) -> crate::Result<Command> {
+    let mut protocol = Protocol::new();
+    protocol.send_raw(lines)?;
+    if protocol.in_list() {
+        return Err(Error::Custom(
+            "expected a complete command list, found no `command_list_end`".to_string(),
+        ));
+    }
+    let mut commands = protocol.in_flight();
+    match (commands.next(), commands.next()) {
+        (Some(command), None) => Ok(command.clone()),
+        (None, _) => Err(Error::Custom("expected a command, found none".to_string())),
+        (Some(_), Some(_)) => Err(Error::Custom(
+            "expected a single command or command list, found more".to_string(),
+        )),
+    }
+}
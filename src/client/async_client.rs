@@ -0,0 +1,410 @@
+use super::{check_complete, SessionConfig};
+use crate::{
+    error::Io,
+    filter::Filter,
+    model::{Changed, Request, Song, Status, Subsystem, TagTypes},
+    protocol::{Command, Protocol, Response},
+    read::Slice,
+    CommandSerializer, Error, RequestDeserializer, Tag, Version,
+};
+use futures_core::Stream;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{mpsc, oneshot},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An asynchronous client, pipelining commands over a stream like
+/// `tokio::net::TcpStream`.
+///
+/// The stream is owned by a task spawned by [`AsyncClient::new`], which writes commands as soon as
+/// they are issued and hands the responses to the callers in order. While no command is pending
+/// the task keeps the connection in `idle` and leaves it with `noidle` once a command is issued.
+///
+/// Clones share the connection, which is closed when all of them were dropped.
+#[derive(Clone, Debug)]
+pub struct AsyncClient {
+    jobs: mpsc::UnboundedSender<Job>,
+    version: Version,
+    /// Tags enabled by [`SessionConfig::tags`].
+    tags: Option<Vec<Tag>>,
+}
+
+#[derive(Debug)]
+enum Job {
+    /// A command to send, along with where to deliver its response.
+    Command {
+        lines: Vec<u8>,
+        reply: oneshot::Sender<crate::Result<Response>>,
+    },
+    /// Registers an [`AsyncIdleWatcher`].
+    Watch(mpsc::UnboundedSender<crate::Result<BTreeSet<Subsystem>>>),
+}
+
+impl AsyncClient {
+    /// Reads the greeting of the server from `stream` and spawns the task driving the connection.
+    ///
+    /// Has to be called from within a tokio runtime.
+    pub async fn new<S>(stream: S) -> crate::Result<Self>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        AsyncClient::with_session(stream, &SessionConfig::default()).await
+    }
+
+    /// Reads the greeting of the server from `stream`, sets up the session and spawns the task
+    /// driving the connection.
+    ///
+    /// The session is set up before the connection is kept in `idle`, which may require the
+    /// password. Fails like [`Client::with_session`](super::Client::with_session).
+    pub async fn with_session<S>(mut stream: S, config: &SessionConfig) -> crate::Result<Self>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut protocol = Protocol::new();
+        while protocol.version().is_none() {
+            receive(&mut stream, &mut protocol).await?;
+            protocol.poll()?;
+        }
+        let version = protocol.version().unwrap_or_default();
+
+        let mut setup = Setup {
+            stream: &mut stream,
+            protocol: &mut protocol,
+            version,
+        };
+        let mut tags = None;
+        if let Some(password) = &config.password {
+            setup.command::<_, ()>(&Request::Password(password)).await?;
+        }
+        if let Some(enable) = &config.tags {
+            if version >= SessionConfig::TAGTYPES_CLEAR {
+                setup
+                    .command::<_, ()>(&Request::Tagtypes("clear", &[]))
+                    .await?;
+                if !enable.is_empty() {
+                    setup
+                        .command::<_, ()>(&Request::Tagtypes("enable", enable))
+                        .await?;
+                }
+                let enabled: TagTypes = setup.command(&Request::TagtypesList).await?;
+                tags = Some(enabled.tagtype);
+            }
+        }
+        if let Some(limit) = config.binary_limit {
+            if version >= SessionConfig::BINARYLIMIT {
+                setup.command::<_, ()>(&Request::Binarylimit(limit)).await?;
+            }
+        }
+
+        let (jobs, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(drive(stream, protocol, receiver));
+        Ok(AsyncClient {
+            jobs,
+            version,
+            tags,
+        })
+    }
+
+    /// Returns the protocol version the server announced in its greeting.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the tags that are enabled, if they were restricted by [`SessionConfig::tags`].
+    ///
+    /// Songs returned by [`AsyncClient::current_song`], [`AsyncClient::queue`] and
+    /// [`AsyncClient::find`] only contain these tags.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.tags.as_deref()
+    }
+
+    /// Sends `command`, serialized with [`CommandSerializer`], and deserializes its response as
+    /// `T`.
+    ///
+    /// The command is written right away, so commands issued concurrently are pipelined.
+    pub async fn command<C, T>(&self, command: &C) -> crate::Result<T>
+    where
+        C: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self.raw_command(command).await?;
+        T::deserialize(&mut self.deserializer(&response))
+    }
+
+    /// Sends already serialized command lines, terminated by a newline, and returns the raw
+    /// response, which may be an `ACK`.
+    ///
+    /// The lines have to make up complete commands or command lists, otherwise they are rejected,
+    /// and must not include `idle` or `noidle`, which are sent by the client itself.
+    pub async fn send_raw(&self, lines: Vec<u8>) -> crate::Result<Response> {
+        let command = check_complete(&lines)?;
+        let names = match &command {
+            Command::Single(name) => std::slice::from_ref(name),
+            Command::List { commands, .. } => commands.as_slice(),
+        };
+        if let Some(name) = names
+            .iter()
+            .find(|name| *name == "idle" || *name == "noidle")
+        {
+            return Err(Error::Custom(format!(
+                "can't send `{name}`, the connection is kept in `idle` by the client itself"
+            )));
+        }
+        let (reply, response) = oneshot::channel();
+        self.jobs
+            .send(Job::Command { lines, reply })
+            .map_err(|_| Error::Eof)?;
+        response.await.map_err(|_| Error::Eof)?
+    }
+
+    pub async fn status(&self) -> crate::Result<Status> {
+        self.command(&Request::Status).await
+    }
+
+    /// Returns the current song, or `None` if there is none.
+    pub async fn current_song(&self) -> crate::Result<Option<Song>> {
+        self.songs(&Request::Currentsong).await
+    }
+
+    /// Starts playing the song at `pos` in the queue, or the current song.
+    pub async fn play(&self, pos: Option<u32>) -> crate::Result<()> {
+        self.command(&Request::Play(pos)).await
+    }
+
+    /// Returns the songs in the queue.
+    pub async fn queue(&self) -> crate::Result<Vec<Song>> {
+        self.songs(&Request::Playlistinfo).await
+    }
+
+    /// Returns the songs in the database matching `filter`.
+    pub async fn find(&self, filter: &Filter) -> crate::Result<Vec<Song>> {
+        self.songs(&Request::Find(filter)).await
+    }
+
+    /// Sends `request` and deserializes its response as `T`, skipping tags that aren't enabled.
+    async fn songs<T: DeserializeOwned>(&self, request: &Request<'_>) -> crate::Result<T> {
+        let response = self.raw_command(request).await?;
+        T::deserialize(&mut self.deserializer(&response).tags(self.tags.clone()))
+    }
+
+    /// Sends `command`, serialized with [`CommandSerializer`], and returns its raw response.
+    async fn raw_command<C: Serialize + ?Sized>(&self, command: &C) -> crate::Result<Response> {
+        let mut ser = CommandSerializer::from_write(Vec::new());
+        command.serialize(&mut ser)?;
+        self.send_raw(ser.into_inner()).await
+    }
+
+    fn deserializer<'de>(&self, response: &'de Response) -> RequestDeserializer<'de, Slice<'de>> {
+        RequestDeserializer::from_read(Slice {
+            src: &response.data,
+        })
+        .version(Some(self.version))
+    }
+
+    /// Watches `subsystems` or, if there are none, all of them for changes reported by the
+    /// `idle` the connection is kept in.
+    pub fn watch(&self, subsystems: Vec<Subsystem>) -> AsyncIdleWatcher {
+        let (watch, changes) = mpsc::unbounded_channel();
+        // If the connection is closed already, the stream just ends.
+        let _ = self.jobs.send(Job::Watch(watch));
+        AsyncIdleWatcher {
+            changes,
+            subsystems,
+        }
+    }
+}
+
+/// A stream of the sets of changed subsystems, returned by [`AsyncClient::watch`].
+///
+/// It ends once the connection is closed. Dropping it cancels watching, without affecting the
+/// client.
+#[derive(Debug)]
+pub struct AsyncIdleWatcher {
+    changes: mpsc::UnboundedReceiver<crate::Result<BTreeSet<Subsystem>>>,
+    subsystems: Vec<Subsystem>,
+}
+
+impl AsyncIdleWatcher {
+    /// Waits for the next set of changed subsystems, like [`Stream::poll_next`].
+    pub async fn changed(&mut self) -> Option<crate::Result<BTreeSet<Subsystem>>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for AsyncIdleWatcher {
+    type Item = crate::Result<BTreeSet<Subsystem>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let changed = match self.changes.poll_recv(cx) {
+                Poll::Ready(Some(Ok(mut changed))) => {
+                    if !self.subsystems.is_empty() {
+                        changed.retain(|subsystem| self.subsystems.contains(subsystem));
+                    }
+                    changed
+                }
+                poll => return poll,
+            };
+            if !changed.is_empty() {
+                return Poll::Ready(Some(Ok(changed)));
+            }
+        }
+    }
+}
+
+/// The connection while the session is set up, before the task driving it is spawned.
+struct Setup<'a, S> {
+    stream: &'a mut S,
+    protocol: &'a mut Protocol,
+    version: Version,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Setup<'_, S> {
+    /// Sends `command` and deserializes its response as `T`.
+    async fn command<C, T>(&mut self, command: &C) -> crate::Result<T>
+    where
+        C: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.protocol.send(command)?;
+        self.stream
+            .write_all(&self.protocol.take_outgoing())
+            .await?;
+        let response = loop {
+            match self.protocol.poll()? {
+                Some(response) => break response,
+                None => receive(self.stream, self.protocol).await?,
+            }
+        };
+        T::deserialize(
+            &mut RequestDeserializer::from_read(Slice {
+                src: &response.data,
+            })
+            .version(Some(self.version)),
+        )
+    }
+}
+
+/// Writes the commands of `jobs` and delivers their responses until the connection fails or all
+/// clients were dropped.
+async fn drive<S>(stream: S, mut protocol: Protocol, mut jobs: mpsc::UnboundedReceiver<Job>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    // One entry per command in flight, `None` for the `idle` sent by the task itself.
+    let mut replies = VecDeque::new();
+    let mut watchers = Vec::new();
+    let result: crate::Result<()> = async {
+        loop {
+            if replies.is_empty() {
+                protocol.send_raw(b"idle\n")?;
+                replies.push_back(None);
+                writer.write_all(&protocol.take_outgoing()).await?;
+            }
+
+            tokio::select! {
+                job = jobs.recv() => {
+                    let (lines, reply) = match job {
+                        Some(Job::Command { lines, reply }) => (lines, reply),
+                        Some(Job::Watch(watcher)) => {
+                            watchers.push(watcher);
+                            continue;
+                        }
+                        None => return Ok(()),
+                    };
+                    // Sent along with the command, so neither is sent if it's rejected.
+                    let lines = if protocol.is_idle() {
+                        [b"noidle\n", lines.as_slice()].concat()
+                    } else {
+                        lines
+                    };
+                    let in_flight = protocol.in_flight().count();
+                    match protocol.send_raw(&lines) {
+                        Ok(()) => {
+                            replies.push_back(Some(reply));
+                            let sent = protocol.in_flight().count() - in_flight;
+                            replies.extend((1..sent).map(|_| None));
+                        }
+                        Err(error) => {
+                            let _ = reply.send(Err(error));
+                        }
+                    }
+                    writer.write_all(&protocol.take_outgoing()).await?;
+                }
+                received = receive(&mut reader, &mut protocol) => {
+                    received?;
+                    while let Some(response) = protocol.poll()? {
+                        match replies.pop_front() {
+                            Some(Some(reply)) => {
+                                let _ = reply.send(Ok(response));
+                            }
+                            Some(None) if response.command.name() == "idle" => {
+                                notify(&mut watchers, &response);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+    .await;
+
+    if let Err(error) = result {
+        for reply in replies.into_iter().flatten() {
+            let _ = reply.send(Err(copy_error(&error)));
+        }
+    }
+}
+
+/// Hands the changes reported by the response to an `idle` to the watchers that weren't dropped.
+fn notify(
+    watchers: &mut Vec<mpsc::UnboundedSender<crate::Result<BTreeSet<Subsystem>>>>,
+    response: &Response,
+) {
+    let changed = response
+        .deserialize::<Changed>()
+        .map(|changed| changed.changed.into_iter().collect::<BTreeSet<_>>());
+    watchers.retain(|watcher| match &changed {
+        Ok(changed) if changed.is_empty() => !watcher.is_closed(),
+        Ok(changed) => watcher.send(Ok(changed.clone())).is_ok(),
+        Err(error) => watcher.send(Err(copy_error(error))).is_ok(),
+    });
+}
+
+/// Hands the next chunk read from `reader` to `protocol`.
+async fn receive<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    protocol: &mut Protocol,
+) -> crate::Result<()> {
+    let mut buffer = [0; 4096];
+    let len = reader.read(&mut buffer).await?;
+    if len == 0 {
+        return Err(Error::Eof);
+    }
+    protocol.receive(&buffer[..len]);
+    Ok(())
+}
+
+/// Copies the error that failed the connection for every caller waiting for a response.
+fn copy_error(error: &Error) -> Error {
+    match error {
+        Error::Custom(message) => Error::Custom(message.clone()),
+        Error::Syntax(message) => Error::Syntax(message.clone()),
+        Error::Io(Io(error)) => Error::Io(Io(io::Error::new(error.kind(), error.to_string()))),
+        Error::Ack(ack) => Error::Ack(ack.clone()),
+        Error::Pending => Error::Pending,
+        Error::Eof => Error::Eof,
+    }
+}
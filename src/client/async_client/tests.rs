@@ -0,0 +1,233 @@
+use super::{AsyncClient, SessionConfig};
+use crate::{
+    model::{State, Subsystem},
+    AckKind, Error, Tag, Version,
+};
+use serde::Serialize;
+use std::{collections::BTreeSet, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+
+/// Runs a fake server, answering each expected command line with its response, which may be
+/// empty to not answer (yet).
+fn serve(script: &'static [(&'static str, &'static str)]) -> (DuplexStream, JoinHandle<()>) {
+    let (client, server) = tokio::io::duplex(4096);
+    let handle = tokio::spawn(async move {
+        let (reader, mut writer) = tokio::io::split(server);
+        writer.write_all(b"OK MPD 0.23.5\n").await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        for (command, response) in script {
+            assert_eq!(lines.next_line().await.unwrap().unwrap(), *command);
+            writer.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (client, handle)
+}
+
+#[tokio::test]
+async fn pipelined() {
+    let (stream, server) = serve(&[
+        ("idle", ""),
+        ("noidle", "OK\n"),
+        (
+            "status",
+            "volume: 50\nrepeat: 0\nrandom: 1\nsingle: 0\nconsume: 0\nplaylist: 3\n\
+             playlistlength: 2\nstate: pause\nOK\n",
+        ),
+        ("play 1", "OK\n"),
+        ("currentsong", "file: a.flac\nTitle: A\nOK\n"),
+        ("idle", ""),
+    ]);
+    let client = AsyncClient::new(stream).await.unwrap();
+    assert_eq!(client.version(), Version::new(0, 23, 5));
+
+    let (status, play, song) =
+        tokio::join!(client.status(), client.play(Some(1)), client.current_song());
+    let status = status.unwrap();
+    assert!(status.random);
+    assert_eq!(status.state, State::Pause);
+    play.unwrap();
+    assert_eq!(song.unwrap().unwrap().title.as_deref(), Some("A"));
+
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn session() {
+    let (stream, server) = serve(&[
+        ("password secret", "OK\n"),
+        ("tagtypes clear", "OK\n"),
+        ("tagtypes enable Artist Title", "OK\n"),
+        ("tagtypes", "tagtype: Artist\ntagtype: Title\nOK\n"),
+        ("binarylimit 1048576", "OK\n"),
+        // The connection is only kept in `idle` once the session is set up.
+        ("idle", ""),
+        ("noidle", "OK\n"),
+        (
+            "currentsong",
+            "file: a.flac\nArtist: A\nAlbum: B\nTitle: C\nOK\n",
+        ),
+        ("idle", ""),
+    ]);
+    let config = SessionConfig {
+        password: Some("secret".to_string()),
+        tags: Some(vec![Tag::Artist, Tag::Title]),
+        binary_limit: Some(1 << 20),
+    };
+    let client = AsyncClient::with_session(stream, &config).await.unwrap();
+    assert_eq!(client.tags(), Some(&[Tag::Artist, Tag::Title][..]));
+    // `Album` isn't enabled, so it is skipped even though the server sent it.
+    let song = client.current_song().await.unwrap().unwrap();
+    assert_eq!(
+        (song.artist, song.album, song.title),
+        (vec!["A".to_string()], None, Some("C".to_string()))
+    );
+    server.await.unwrap();
+
+    let (stream, server) = serve(&[(
+        "password wrong",
+        "ACK [3@0] {password} incorrect password\n",
+    )]);
+    let config = SessionConfig {
+        password: Some("wrong".to_string()),
+        ..SessionConfig::default()
+    };
+    match AsyncClient::with_session(stream, &config).await {
+        Err(Error::Ack(ack)) => assert_eq!(ack.kind(), AckKind::Password),
+        result => panic!("expected a password ack, found {result:?}"),
+    }
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn idle_changes() {
+    let (stream, server) = tokio::io::duplex(4096);
+    let (idle, idled) = oneshot::channel();
+    let server = tokio::spawn(async move {
+        let (reader, mut writer) = tokio::io::split(server);
+        writer.write_all(b"OK MPD 0.23.5\n").await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+        writer.write_all(b"changed: player\nOK\n").await.unwrap();
+        // The client enters `idle` again after receiving the changes.
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+        idle.send(()).unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "noidle");
+        writer.write_all(b"changed: mixer\nOK\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "currentsong");
+        writer.write_all(b"OK\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+    });
+    let client = AsyncClient::new(stream).await.unwrap();
+    idled.await.unwrap();
+
+    assert_eq!(client.current_song().await.unwrap(), None);
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn ack_and_eof() {
+    let (stream, server) = serve(&[
+        ("idle", ""),
+        ("noidle", "OK\n"),
+        ("play 5", "ACK [2@0] {play} Bad song index\n"),
+        ("status", ""),
+    ]);
+    let client = AsyncClient::new(stream).await.unwrap();
+
+    let (play, status) = tokio::join!(client.play(Some(5)), client.status());
+    let Err(Error::Ack(ack)) = play else {
+        panic!("expected an ack, found {play:?}");
+    };
+    assert_eq!(ack.kind(), AckKind::Arg);
+    assert_eq!(ack.message, "Bad song index");
+    // The server closes the connection without answering `status`.
+    assert_eq!(status.unwrap_err(), Error::Eof);
+
+    server.await.unwrap();
+    assert_eq!(client.status().await.unwrap_err(), Error::Eof);
+}
+
+#[tokio::test]
+async fn rejected_command() {
+    #[derive(Serialize)]
+    enum Raw {
+        #[serde(rename = "command_list_end")]
+        End,
+    }
+
+    let (stream, server) = serve(&[
+        ("idle", ""),
+        ("noidle", "OK\n"),
+        ("currentsong", "OK\n"),
+        ("idle", ""),
+    ]);
+    let client = AsyncClient::new(stream).await.unwrap();
+
+    let error = client.command::<_, ()>(&Raw::End).await.unwrap_err();
+    assert!(error.is_data(), "{error:?}");
+    // Would leave the command list open, so the response would be matched to later commands.
+    let error = client
+        .send_raw(b"command_list_begin\nstatus\n".to_vec())
+        .await
+        .unwrap_err();
+    assert!(error.is_data(), "{error:?}");
+    for lines in [
+        &b"noidle\n"[..],
+        b"idle\n",
+        b"command_list_begin\nidle\ncommand_list_end\n",
+    ] {
+        let error = client.send_raw(lines.to_vec()).await.unwrap_err();
+        assert!(error.is_data(), "{error:?}");
+    }
+    let error = client
+        .send_raw(b"status\ncurrentsong\n".to_vec())
+        .await
+        .unwrap_err();
+    assert!(error.is_data(), "{error:?}");
+    assert_eq!(client.current_song().await.unwrap(), None);
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn watch() {
+    let (stream, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        let (reader, mut writer) = tokio::io::split(server);
+        writer.write_all(b"OK MPD 0.23.5\n").await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        writer.write_all(b"changed: player\nOK\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+        writer
+            .write_all(b"changed: mixer\nchanged: options\nOK\n")
+            .await
+            .unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "idle");
+    });
+    let client = AsyncClient::new(stream).await.unwrap();
+    let mut all = client.watch(Vec::new());
+    let mut mixer = client.watch(vec![Subsystem::Mixer]);
+
+    assert_eq!(
+        all.changed().await.unwrap().unwrap(),
+        BTreeSet::from([Subsystem::Player])
+    );
+    assert_eq!(
+        all.changed().await.unwrap().unwrap(),
+        BTreeSet::from([Subsystem::Mixer, Subsystem::Options])
+    );
+    // The change of the player was filtered out.
+    assert_eq!(
+        mixer.changed().await.unwrap().unwrap(),
+        BTreeSet::from([Subsystem::Mixer])
+    );
+
+    server.await.unwrap();
+    drop(client);
+    assert!(all.changed().await.is_none());
+}
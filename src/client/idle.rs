@@ -0,0 +1,116 @@
+use super::{Client, Stream};
+use crate::model::{Changed, Request, Subsystem};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Debug, Formatter},
+    io::{self, Write},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Waits for changes with `idle`, as an iterator over the sets of changed subsystems.
+///
+/// Every call to [`Iterator::next`] blocks until one of the watched subsystems changed. It can be
+/// canceled from another thread with a [`Canceler`], after which the iterator ends and the client
+/// can be used for other commands again.
+#[derive(Debug)]
+pub struct IdleWatcher<S> {
+    client: Client<S>,
+    subsystems: Vec<Subsystem>,
+    cancel: Arc<Mutex<Cancel>>,
+}
+
+/// Whether the watcher was canceled, shared with its [`Canceler`]s.
+#[derive(Default, Debug)]
+struct Cancel {
+    canceled: bool,
+    /// Whether an `idle` is in flight, which has to be left with `noidle`.
+    idle: bool,
+}
+
+impl<S: io::Read + io::Write> IdleWatcher<S> {
+    /// Watches `subsystems` or, if there are none, all of them.
+    pub fn new(client: Client<S>, subsystems: Vec<Subsystem>) -> Self {
+        IdleWatcher {
+            client,
+            subsystems,
+            cancel: Arc::default(),
+        }
+    }
+
+    /// Returns the client, which is never idle in between calls to [`Iterator::next`].
+    pub fn into_client(self) -> Client<S> {
+        self.client
+    }
+
+    /// Returns a [`Canceler`] that leaves the `idle` with `noidle` written to `stream`, which has
+    /// to be a handle to the same connection.
+    pub fn canceler_with<W: io::Write + Send + 'static>(&self, stream: W) -> Canceler {
+        Canceler {
+            cancel: Arc::clone(&self.cancel),
+            stream: Arc::new(Mutex::new(Box::new(stream))),
+        }
+    }
+}
+
+impl IdleWatcher<Stream> {
+    pub fn canceler(&self) -> io::Result<Canceler> {
+        Ok(self.canceler_with(self.client.stream.try_clone()?))
+    }
+}
+
+impl<S: io::Read + io::Write> Iterator for IdleWatcher<S> {
+    type Item = crate::Result<BTreeSet<Subsystem>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            {
+                let mut cancel = self.cancel.lock().unwrap_or_else(PoisonError::into_inner);
+                if cancel.canceled {
+                    return None;
+                }
+                if let Err(error) = self.client.send(&Request::Idle(&self.subsystems)) {
+                    return Some(Err(error));
+                }
+                cancel.idle = true;
+            }
+
+            let changed = self.client.response::<Changed>();
+            self.cancel
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .idle = false;
+            match changed {
+                // `noidle` was sent, either by a `Canceler` or one that came too late.
+                Ok(Changed { changed }) if changed.is_empty() => {}
+                Ok(Changed { changed }) => return Some(Ok(changed.into_iter().collect())),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Cancels an [`IdleWatcher`], which may be blocked in another thread.
+#[derive(Clone)]
+pub struct Canceler {
+    cancel: Arc<Mutex<Cancel>>,
+    stream: Arc<Mutex<Box<dyn io::Write + Send>>>,
+}
+
+impl Canceler {
+    /// Ends the iterator, waking it up with `noidle` if it is waiting for changes.
+    pub fn cancel(&self) -> io::Result<()> {
+        let mut cancel = self.cancel.lock().unwrap_or_else(PoisonError::into_inner);
+        cancel.canceled = true;
+        if cancel.idle {
+            let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+            stream.write_all(b"noidle\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Canceler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Canceler").finish_non_exhaustive()
+    }
+}
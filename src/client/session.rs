@@ -0,0 +1,25 @@
+use crate::{Tag, Version};
+
+/// Setup of a session, applied right after the greeting, see
+/// [`Client::with_session`](crate::client::Client::with_session).
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct SessionConfig {
+    /// Password to send with `password`.
+    pub password: Option<String>,
+    /// Tags to enable with `tagtypes clear` and `tagtypes enable`, all tags the server supports
+    /// are enabled if `None`.
+    ///
+    /// Ignored for servers older than 0.21, which don't support `tagtypes clear`.
+    pub tags: Option<Vec<Tag>>,
+    /// Maximum size of binary responses to set with `binarylimit`.
+    ///
+    /// Ignored for servers older than 0.22.4, which don't support `binarylimit`.
+    pub binary_limit: Option<u64>,
+}
+
+impl SessionConfig {
+    /// Version that added `tagtypes clear`.
+    pub(crate) const TAGTYPES_CLEAR: Version = Version::new(0, 21, 0);
+    /// Version that added `binarylimit`.
+    pub(crate) const BINARYLIMIT: Version = Version::new(0, 22, 4);
+}
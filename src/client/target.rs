@@ -0,0 +1,170 @@
+use crate::Error;
+use std::{
+    env,
+    fmt::Arguments,
+    io::{self, IoSlice, IoSliceMut},
+    net::TcpStream,
+    path::{Path, PathBuf},
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+#[cfg(test)]
+mod tests;
+
+pub const DEFAULT_HOST: &str = "localhost";
+pub const DEFAULT_PORT: u16 = 6600;
+
+/// Where to connect to, as configured by `MPD_HOST` and `MPD_PORT`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ConnectionTarget {
+    /// Password to send after connecting.
+    pub password: Option<String>,
+    pub address: Address,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Address {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    /// Path of a Unix socket.
+    Unix(PathBuf),
+    /// Name of a Linux abstract socket, without the leading `@`.
+    Abstract(String),
+}
+
+impl ConnectionTarget {
+    /// Parses `MPD_HOST`, `MPD_PORT` and falls back to `$XDG_RUNTIME_DIR/mpd/socket`.
+    pub fn from_env() -> crate::Result<Self> {
+        let host = env::var("MPD_HOST").ok();
+        let port = env::var("MPD_PORT").ok();
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        Self::parse(host.as_deref(), port.as_deref(), runtime_dir.as_deref())
+    }
+
+    /// Parses the values of `MPD_HOST`, `MPD_PORT` and `XDG_RUNTIME_DIR`.
+    ///
+    /// `host` is either a host name, the absolute path of a Unix socket or `@name` for an abstract
+    /// socket, optionally preceded by `password@`. Without `host`, the socket `mpd/socket` in
+    /// `runtime_dir` is used if it exists, `localhost` otherwise. `port` defaults to 6600.
+    pub fn parse(
+        host: Option<&str>,
+        port: Option<&str>,
+        runtime_dir: Option<&Path>,
+    ) -> crate::Result<Self> {
+        let port = match port.filter(|port| !port.is_empty()) {
+            Some(port) => port
+                .parse()
+                .map_err(|_| Error::Syntax(format!("invalid port {port:?}")))?,
+            None => DEFAULT_PORT,
+        };
+
+        let Some(host) = host.filter(|host| !host.is_empty()) else {
+            let socket = runtime_dir.map(|dir| dir.join("mpd").join("socket"));
+            let address = match socket {
+                Some(socket) if socket.exists() => Address::Unix(socket),
+                _ => Address::Tcp {
+                    host: DEFAULT_HOST.to_string(),
+                    port,
+                },
+            };
+            return Ok(ConnectionTarget {
+                password: None,
+                address,
+            });
+        };
+
+        let (password, host) = match host.split_once('@') {
+            Some((password, host)) if !password.is_empty() => (Some(password.to_string()), host),
+            _ => (None, host),
+        };
+        let address = if let Some(name) = host.strip_prefix('@') {
+            Address::Abstract(name.to_string())
+        } else if host.starts_with('/') {
+            Address::Unix(PathBuf::from(host))
+        } else if host.is_empty() {
+            return Err(Error::Syntax("missing host after password".to_string()));
+        } else {
+            Address::Tcp {
+                host: host.to_string(),
+                port,
+            }
+        };
+        Ok(ConnectionTarget { password, address })
+    }
+
+    /// Opens a stream to the address.
+    pub fn connect(&self) -> io::Result<Stream> {
+        match &self.address {
+            Address::Tcp { host, port } => {
+                TcpStream::connect((host.as_str(), *port)).map(Stream::Tcp)
+            }
+            #[cfg(unix)]
+            Address::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+            #[cfg(target_os = "linux")]
+            Address::Abstract(name) => {
+                use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+                let address = SocketAddr::from_abstract_name(name)?;
+                UnixStream::connect_addr(&address).map(Stream::Unix)
+            }
+            #[allow(unreachable_patterns)]
+            address => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{address:?} isn't supported on this platform"),
+            )),
+        }
+    }
+}
+
+/// A stream opened by [`ConnectionTarget::connect`].
+#[derive(Debug)]
+pub enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    /// Returns a new handle to the same socket.
+    pub fn try_clone(&self) -> io::Result<Stream> {
+        match self {
+            Stream::Tcp(stream) => stream.try_clone().map(Stream::Tcp),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.try_clone().map(Stream::Unix),
+        }
+    }
+}
+
+macro_rules! forward_to_stream {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(&mut self, $($arg: $ty),*) -> $ret {
+                match self {
+                    Stream::Tcp(stream) => stream.$method($($arg),*),
+                    #[cfg(unix)]
+                    Stream::Unix(stream) => stream.$method($($arg),*),
+                }
+            }
+        )*
+    };
+}
+
+impl io::Read for Stream {
+    forward_to_stream! {
+        read(buf: &mut [u8]) -> io::Result<usize>;
+        read_vectored(bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize>;
+    }
+}
+
+impl io::Write for Stream {
+    forward_to_stream! {
+        write(buf: &[u8]) -> io::Result<usize>;
+        write_vectored(bufs: &[IoSlice<'_>]) -> io::Result<usize>;
+        flush() -> io::Result<()>;
+        write_all(buf: &[u8]) -> io::Result<()>;
+        write_fmt(args: Arguments<'_>) -> io::Result<()>;
+    }
+}
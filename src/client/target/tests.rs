@@ -0,0 +1,111 @@
+use super::{Address, ConnectionTarget};
+use crate::Error;
+use std::path::PathBuf;
+
+fn tcp(host: &str, port: u16) -> Address {
+    Address::Tcp {
+        host: host.to_string(),
+        port,
+    }
+}
+
+fn parse(host: Option<&str>, port: Option<&str>) -> crate::Result<ConnectionTarget> {
+    ConnectionTarget::parse(host, port, None)
+}
+
+#[test]
+fn hosts() {
+    assert_eq!(
+        parse(Some("music.local"), Some("6601")),
+        Ok(ConnectionTarget {
+            password: None,
+            address: tcp("music.local", 6601),
+        })
+    );
+    assert_eq!(
+        parse(Some("secret@music.local"), None),
+        Ok(ConnectionTarget {
+            password: Some("secret".to_string()),
+            address: tcp("music.local", 6600),
+        })
+    );
+    assert_eq!(
+        parse(Some("/run/mpd/socket"), None).map(|target| target.address),
+        Ok(Address::Unix(PathBuf::from("/run/mpd/socket")))
+    );
+    assert_eq!(
+        parse(Some("secret@/run/mpd/socket"), None),
+        Ok(ConnectionTarget {
+            password: Some("secret".to_string()),
+            address: Address::Unix(PathBuf::from("/run/mpd/socket")),
+        })
+    );
+    assert_eq!(
+        parse(Some("@mpd"), None),
+        Ok(ConnectionTarget {
+            password: None,
+            address: Address::Abstract("mpd".to_string()),
+        })
+    );
+    assert_eq!(
+        parse(Some("secret@@mpd"), None),
+        Ok(ConnectionTarget {
+            password: Some("secret".to_string()),
+            address: Address::Abstract("mpd".to_string()),
+        })
+    );
+}
+
+#[test]
+fn defaults() {
+    assert_eq!(
+        parse(None, None).map(|target| target.address),
+        Ok(tcp("localhost", 6600))
+    );
+    assert_eq!(
+        parse(Some(""), Some("")).map(|target| target.address),
+        Ok(tcp("localhost", 6600))
+    );
+
+    let runtime_dir = std::env::temp_dir().join(format!("serde_mpd-{}", std::process::id()));
+    std::fs::create_dir_all(runtime_dir.join("mpd")).unwrap();
+    std::fs::write(runtime_dir.join("mpd/socket"), b"").unwrap();
+    assert_eq!(
+        ConnectionTarget::parse(None, None, Some(&runtime_dir)).map(|target| target.address),
+        Ok(Address::Unix(runtime_dir.join("mpd/socket")))
+    );
+    assert_eq!(
+        ConnectionTarget::parse(Some("music.local"), None, Some(&runtime_dir))
+            .map(|target| target.address),
+        Ok(tcp("music.local", 6600))
+    );
+    std::fs::remove_dir_all(&runtime_dir).unwrap();
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(
+        parse(Some("music.local"), Some("mpd")),
+        Err(Error::Syntax("invalid port \"mpd\"".to_string()))
+    );
+    assert_eq!(
+        parse(Some("secret@"), None),
+        Err(Error::Syntax("missing host after password".to_string()))
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn connect_abstract() {
+    use std::os::{
+        linux::net::SocketAddrExt,
+        unix::net::{SocketAddr, UnixListener},
+    };
+
+    let name = format!("serde_mpd-{}", std::process::id());
+    let listener =
+        UnixListener::bind_addr(&SocketAddr::from_abstract_name(&name).unwrap()).unwrap();
+    let target = parse(Some(&format!("@{name}")), None).unwrap();
+    let _stream = target.connect().unwrap();
+    listener.accept().unwrap();
+}
@@ -0,0 +1,230 @@
+use super::{Client, IdleWatcher, SessionConfig, Stream};
+use crate::{
+    filter::{Filter, TagOperator},
+    model::{Song, State, Subsystem},
+    Ack, AckKind, Error, Tag, Version,
+};
+use std::{
+    collections::BTreeSet,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    sync::mpsc,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Runs a fake server, answering each expected command line with its response.
+fn serve(script: &'static [(&'static str, &'static str)]) -> (UnixStream, JoinHandle<()>) {
+    let (client, server) = UnixStream::pair().unwrap();
+    let handle = thread::spawn(move || {
+        let mut writer = server.try_clone().unwrap();
+        writer.write_all(b"OK MPD 0.23.5\n").unwrap();
+        let mut lines = BufReader::new(server).lines();
+        for (command, response) in script {
+            assert_eq!(lines.next().unwrap().unwrap(), *command);
+            writer.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (client, handle)
+}
+
+#[test]
+fn commands() {
+    let (stream, server) = serve(&[
+        (
+            "status",
+            "volume: 50\nrepeat: 0\nrandom: 1\nsingle: oneshot\nconsume: 0\nplaylist: 3\n\
+             playlistlength: 2\nstate: play\nsong: 1\nsongid: 2\nelapsed: 1.5\nOK\n",
+        ),
+        ("currentsong", "OK\n"),
+        ("play 1", "OK\n"),
+        (
+            "playlistinfo",
+            "file: a.flac\nArtist: A\nArtist: B\nPos: 0\nId: 1\nfile: b.flac\nTitle: B\n\
+             Pos: 1\nId: 2\nOK\n",
+        ),
+        (
+            "find \"(Artist == \\\"A\\\")\"",
+            "file: a.flac\nLast-Modified: 2024-01-01T00:00:00Z\nOK\n",
+        ),
+    ]);
+    let mut client = Client::new(stream).unwrap();
+    assert_eq!(client.version(), Version::new(0, 23, 5));
+
+    let status = client.status().unwrap();
+    assert_eq!(status.volume, Some(50));
+    assert!(status.random && !status.repeat);
+    assert_eq!(status.state, State::Play);
+    assert_eq!((status.song, status.elapsed), (Some(1), Some(1.5)));
+
+    assert_eq!(client.current_song(), Ok(None));
+    assert_eq!(client.play(Some(1)), Ok(()));
+
+    let queue = client.queue().unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue[0].artist, ["A", "B"]);
+    assert_eq!(
+        (queue[1].title.as_deref(), queue[1].pos),
+        (Some("B"), Some(1))
+    );
+
+    let songs = client
+        .find(&Filter::tag("Artist", TagOperator::Equal, "A"))
+        .unwrap();
+    assert_eq!(
+        songs,
+        [Song {
+            file: "a.flac".to_string(),
+            last_modified: Some("2024-01-01T00:00:00Z".to_string()),
+            duration: None,
+            artist: Vec::new(),
+            album: None,
+            title: None,
+            track: None,
+            pos: None,
+            id: None,
+        }]
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn ack_and_eof() {
+    let (stream, server) = serve(&[("play 7", "ACK [2@0] {play} Bad song index\n")]);
+    let mut client = Client::new(stream).unwrap();
+    assert_eq!(
+        client.play(Some(7)),
+        Err(Error::Ack(Ack {
+            code: 2,
+            index: 0,
+            command: "play".to_string(),
+            message: "Bad song index".to_string(),
+        }))
+    );
+    server.join().unwrap();
+    let error = client.status().unwrap_err();
+    assert!(error.is_eof() || error.is_io(), "{error:?}");
+}
+
+#[test]
+fn raw() {
+    let (stream, server) = serve(&[
+        ("outputs", "outputid: 0\noutputname: A\nOK\n"),
+        ("play 7", "ACK [2@0] {play} Bad song index\n"),
+    ]);
+    let mut client = Client::new(stream).unwrap();
+    let response = client.send_raw(b"outputs\n").unwrap();
+    assert_eq!(response.command.name(), "outputs");
+    assert_eq!(response.data, b"outputid: 0\noutputname: A\nOK\n");
+    let response = client.send_raw(b"play 7\n").unwrap();
+    assert!(response.deserialize::<()>().unwrap_err().is_ack());
+    assert!(client.send_raw(b"status").unwrap_err().is_data());
+    assert!(client
+        .send_raw(b"command_list_begin\nstatus\n")
+        .unwrap_err()
+        .is_data());
+    assert!(client.send_raw(b"status\nstatus\n").unwrap_err().is_data());
+    server.join().unwrap();
+}
+
+#[test]
+fn session() {
+    let (stream, server) = serve(&[
+        ("password secret", "OK\n"),
+        ("tagtypes clear", "OK\n"),
+        ("tagtypes enable Artist Title Foo", "OK\n"),
+        ("tagtypes", "tagtype: Artist\ntagtype: Title\nOK\n"),
+        ("binarylimit 1048576", "OK\n"),
+        (
+            "currentsong",
+            "file: a.flac\nArtist: A\nAlbum: B\nTitle: C\nOK\n",
+        ),
+    ]);
+    let config = SessionConfig {
+        password: Some("secret".to_string()),
+        tags: Some(vec![Tag::Artist, Tag::Title, Tag::Other("Foo".to_string())]),
+        binary_limit: Some(1 << 20),
+    };
+    let mut client = Client::with_session(stream, &config).unwrap();
+    assert_eq!(client.tags(), Some(&[Tag::Artist, Tag::Title][..]));
+    // `Album` isn't enabled, so it is skipped even though the server sent it.
+    let song = client.current_song().unwrap().unwrap();
+    assert_eq!(
+        (song.artist, song.album, song.title),
+        (vec!["A".to_string()], None, Some("C".to_string()))
+    );
+    server.join().unwrap();
+}
+
+#[test]
+fn session_failures() {
+    let (stream, server) = serve(&[(
+        "password wrong",
+        "ACK [3@0] {password} incorrect password\n",
+    )]);
+    let config = SessionConfig {
+        password: Some("wrong".to_string()),
+        ..SessionConfig::default()
+    };
+    match Client::with_session(stream, &config) {
+        Err(Error::Ack(ack)) => assert_eq!(ack.kind(), AckKind::Password),
+        result => panic!("expected a password ack, found {result:?}"),
+    }
+    server.join().unwrap();
+
+    let (stream, server) = serve(&[(
+        "tagtypes clear",
+        "ACK [4@0] {tagtypes} you don't have permission for \"tagtypes\"\n",
+    )]);
+    let config = SessionConfig {
+        tags: Some(Vec::new()),
+        ..SessionConfig::default()
+    };
+    match Client::with_session(stream, &config) {
+        Err(Error::Ack(ack)) => assert_eq!(ack.kind(), AckKind::Permission),
+        result => panic!("expected a permission ack, found {result:?}"),
+    }
+    server.join().unwrap();
+}
+
+#[test]
+fn idle_watcher() {
+    let (stream, server) = UnixStream::pair().unwrap();
+    let (idle, idled) = mpsc::channel();
+    let server = thread::spawn(move || {
+        let mut writer = server.try_clone().unwrap();
+        writer.write_all(b"OK MPD 0.23.5\n").unwrap();
+        let mut lines = BufReader::new(server).lines();
+        let mut expect = |line: &str| assert_eq!(lines.next().unwrap().unwrap(), line);
+        expect("idle player mixer");
+        thread::sleep(Duration::from_millis(20));
+        writer.write_all(b"changed: player\nOK\n").unwrap();
+        expect("idle player mixer");
+        idle.send(()).unwrap();
+        expect("noidle");
+        writer.write_all(b"OK\n").unwrap();
+        expect("currentsong");
+        writer.write_all(b"OK\n").unwrap();
+    });
+
+    let client = Client::new(Stream::Unix(stream)).unwrap();
+    let mut watcher = IdleWatcher::new(client, vec![Subsystem::Player, Subsystem::Mixer]);
+    assert_eq!(
+        watcher.next().unwrap().unwrap(),
+        BTreeSet::from([Subsystem::Player])
+    );
+
+    let canceler = watcher.canceler().unwrap();
+    let cancel = thread::spawn(move || {
+        idled.recv().unwrap();
+        canceler.cancel().unwrap();
+    });
+    assert!(watcher.next().is_none());
+    assert!(watcher.next().is_none());
+    cancel.join().unwrap();
+
+    let mut client = watcher.into_client();
+    assert_eq!(client.current_song().unwrap(), None);
+    server.join().unwrap();
+}
@@ -0,0 +1,138 @@
+//! A [`Decoder`] and [`Encoder`] for [`Framed`](tokio_util::codec::Framed) streams.
+
+use crate::{
+    protocol::response_len,
+    read::{Slice, SliceDebug},
+    CommandSerializer, Error, RequestDeserializer, Version,
+};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+use tokio_util::codec::{Decoder, Encoder};
+
+#[cfg(test)]
+mod tests;
+
+/// A complete response, including the `OK`, `list_OK` or `ACK` terminating it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Frame {
+    pub data: Bytes,
+}
+
+impl Frame {
+    /// Returns a deserializer over the response.
+    ///
+    /// The deserializer doesn't know the protocol version, so fields annotated with one (see
+    /// [`Version`]) are optional, unless [`RequestDeserializer::version`] is set to
+    /// [`MpdCodec::version`].
+    pub fn deserializer(&self) -> RequestDeserializer<'_, Slice<'_>> {
+        RequestDeserializer::from_read(Slice { src: &self.data })
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>>(&'de self) -> crate::Result<T> {
+        T::deserialize(&mut self.deserializer())
+    }
+}
+
+/// Decodes the responses of a server, after its greeting, and encodes commands with
+/// [`CommandSerializer`].
+///
+/// Responses are framed at every `OK` and `ACK`, as well as every `list_OK` of a command list
+/// sent with `command_list_ok_begin`. `MpdCodec<Frame>` decodes them as raw [`Frame`]s, any other
+/// `MpdCodec<T>` deserializes them as `T`, with the protocol version of the greeting. Failing to
+/// deserialize a response, e.g. because it is an `ACK`, doesn't fail the stream, so these items are
+/// `Result`s themselves.
+pub struct MpdCodec<T = Frame> {
+    version: Option<Version>,
+    /// Bytes of the current response that were already scanned, see [`response_len`].
+    scanned: usize,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> MpdCodec<T> {
+    pub fn new() -> Self {
+        MpdCodec {
+            version: None,
+            scanned: 0,
+            item: PhantomData,
+        }
+    }
+
+    /// Returns the protocol version the server announced in its greeting, once it was decoded.
+    pub fn version(&self) -> Option<Version> {
+        self.version
+    }
+
+    /// Splits the next complete response off of `src`.
+    fn frame(&mut self, src: &mut BytesMut) -> crate::Result<Option<BytesMut>> {
+        if self.version.is_none() {
+            let Some(end) = src.iter().position(|byte| *byte == b'\n') else {
+                return Ok(None);
+            };
+            let version = src[..end].strip_prefix(b"OK MPD ").ok_or_else(|| {
+                Error::Syntax(format!(
+                    "expected greeting, found {:?}",
+                    SliceDebug(&src[..end])
+                ))
+            })?;
+            self.version = Some(String::from_utf8_lossy(version).parse()?);
+            src.advance(end + 1);
+        }
+
+        let Some(len) = response_len(src, &mut self.scanned, true)? else {
+            return Ok(None);
+        };
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+impl<T> Default for MpdCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for MpdCodec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MpdCodec")
+            .field("version", &self.version)
+            .field("scanned", &self.scanned)
+            .finish()
+    }
+}
+
+impl Decoder for MpdCodec<Frame> {
+    type Item = Frame;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Frame>> {
+        let frame = self.frame(src)?;
+        Ok(frame.map(|data| Frame {
+            data: data.freeze(),
+        }))
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for MpdCodec<T> {
+    type Item = crate::Result<T>;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<crate::Result<T>>> {
+        let Some(data) = self.frame(src)? else {
+            return Ok(None);
+        };
+        let mut de = RequestDeserializer::from_read(Slice { src: &data }).version(self.version);
+        Ok(Some(T::deserialize(&mut de)))
+    }
+}
+
+impl<T, C: Serialize + ?Sized> Encoder<&C> for MpdCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, command: &C, dst: &mut BytesMut) -> crate::Result<()> {
+        command.serialize(&mut CommandSerializer::from_write(dst.writer()))
+    }
+}
@@ -0,0 +1,120 @@
+use super::{Frame, MpdCodec};
+use crate::{model::Song, AckKind, Error, Version};
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes `input` handed to the codec in chunks of `chunk` bytes.
+fn decode_chunked<D: Decoder<Error = Error>>(
+    codec: &mut D,
+    input: &[u8],
+    chunk: usize,
+) -> Vec<D::Item> {
+    let mut src = BytesMut::new();
+    let mut items = Vec::new();
+    for bytes in input.chunks(chunk) {
+        src.extend_from_slice(bytes);
+        while let Some(item) = codec.decode(&mut src).unwrap() {
+            items.push(item);
+        }
+    }
+    assert!(src.is_empty());
+    items
+}
+
+const INPUT: &[u8] = b"OK MPD 0.23.5\n\
+    file: a.flac\nTitle: A\nOK\n\
+    size: 6\nbinary: 3\nOK\n\nOK\n\
+    ACK [50@0] {albumart} No file exists\n\
+    file: b.flac\nlist_OK\nfile: c.flac\nlist_OK\nOK\n";
+
+#[test]
+fn frames() {
+    for chunk in [1, 2, 7, INPUT.len()] {
+        let mut codec = MpdCodec::<Frame>::new();
+        let frames = decode_chunked(&mut codec, INPUT, chunk);
+        assert_eq!(codec.version(), Some(Version::new(0, 23, 5)));
+        assert_eq!(
+            frames
+                .iter()
+                .map(|frame| frame.data.as_ref())
+                .collect::<Vec<_>>(),
+            [
+                b"file: a.flac\nTitle: A\nOK\n".as_slice(),
+                b"size: 6\nbinary: 3\nOK\n\nOK\n",
+                b"ACK [50@0] {albumart} No file exists\n",
+                b"file: b.flac\nlist_OK\n",
+                b"file: c.flac\nlist_OK\n",
+                b"OK\n",
+            ],
+            "chunk size {chunk}"
+        );
+        assert_eq!(
+            frames[0].deserialize::<Song>().unwrap().title.as_deref(),
+            Some("A")
+        );
+    }
+}
+
+#[test]
+fn typed() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Picture {
+        size: u64,
+        #[serde(with = "serde_bytes")]
+        binary: Vec<u8>,
+    }
+
+    let mut codec = MpdCodec::<Picture>::new();
+    let items = decode_chunked(
+        &mut codec,
+        b"OK MPD 0.23.5\nsize: 6\nbinary: 3\nOK\n\nOK\nACK [50@0] {albumart} No file exists\n\
+          size: 1\nOK\n",
+        5,
+    );
+    let [picture, ack, missing] = items.try_into().unwrap();
+    assert_eq!(
+        picture.unwrap(),
+        Picture {
+            size: 6,
+            binary: b"OK\n".to_vec(),
+        }
+    );
+    let Err(Error::Ack(ack)) = ack else {
+        panic!("expected an ack, found {ack:?}");
+    };
+    assert_eq!(ack.kind(), AckKind::NoExist);
+    assert!(missing.unwrap_err().is_data());
+}
+
+#[test]
+fn invalid() {
+    let mut codec = MpdCodec::<Frame>::new();
+    let mut src = BytesMut::from("HELLO\n");
+    assert!(codec.decode(&mut src).unwrap_err().is_syntax());
+
+    let mut codec = MpdCodec::<Frame>::new();
+    let mut src = BytesMut::from("OK MPD 0.23.5\nfile a.flac\n");
+    assert!(codec.decode(&mut src).unwrap_err().is_syntax());
+}
+
+#[test]
+fn encode() {
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Command {
+        Status,
+        Find(&'static str),
+    }
+
+    let mut codec = MpdCodec::<Frame>::new();
+    let mut dst = BytesMut::new();
+    codec.encode(&Command::Status, &mut dst).unwrap();
+    codec
+        .encode(&Command::Find("(Artist == \"A\")"), &mut dst)
+        .unwrap();
+    assert_eq!(
+        dst.as_ref(),
+        b"status\nfind \"(Artist == \\\"A\\\")\"\n".as_slice()
+    );
+}
@@ -1 +1,8 @@
+pub use self::{
+    command::CommandDeserializer,
+    request::{RequestDeserializer, Warning},
+};
+
+mod command;
 mod request;
+mod value;
@@ -0,0 +1,112 @@
+use self::access::CommandAccess;
+use crate::{read::Read, Error};
+use serde::{de::Visitor, forward_to_deserialize_any, Deserializer};
+use std::borrow::Cow;
+
+mod access;
+mod arguments;
+
+#[cfg(test)]
+mod tests;
+
+/// Deserializes the commands a client sends to the server into enums.
+///
+/// The name of the command selects the variant, its arguments are deserialized positionally into
+/// the content of the variant. Quoted arguments are unescaped and arguments of the form `a:b` can be
+/// deserialized as tuples, so `find "(Artist == \"x\")" window 0:5` fits into
+/// `Find(String, String, (u32, Option<u32>))`.
+///
+/// A `command_list_begin` or `command_list_ok_begin` variant is deserialized from all commands up
+/// to the matching `command_list_end`, e.g. into `CommandListBegin(Vec<Command>)`.
+///
+/// Just like [`RequestDeserializer`](crate::RequestDeserializer), the lines of a command that were
+/// already read are kept on [`Error::Pending`], so the deserialization can be retried once more
+/// input is available.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct CommandDeserializer<'de, R> {
+    read: R,
+    scratch: Vec<u8>,
+    /// Arguments of the lines of the command that is currently being deserialized, starting with
+    /// the name of the command.
+    lines: Vec<Vec<Cow<'de, [u8]>>>,
+    /// Index of the next line in `lines` that hasn't been visited yet.
+    cursor: usize,
+}
+
+impl<'de, R: Read<'de>> CommandDeserializer<'de, R> {
+    pub fn from_read(read: R) -> Self {
+        CommandDeserializer {
+            read,
+            scratch: Vec::new(),
+            lines: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    fn peek_line(&mut self) -> crate::Result<&[Cow<'de, [u8]>]> {
+        if self.cursor == self.lines.len() {
+            let line = self.read.read_until(&mut self.scratch, b'\n')?;
+            let arguments = arguments::split(line);
+            self.scratch.clear();
+            let arguments = arguments?;
+            if arguments.is_empty() {
+                return Err(Error::Syntax(
+                    "expected a command, found an empty line".to_string(),
+                ));
+            }
+            self.lines.push(arguments);
+        }
+        Ok(&self.lines[self.cursor])
+    }
+
+    /// Runs `f` from the start of the buffered lines.
+    ///
+    /// Unless `f` returned [`Error::Pending`], all lines it consumed are discarded afterwards. If
+    /// `f` failed, the line it failed on is discarded as well, so the next command can be
+    /// deserialized.
+    fn command<T>(&mut self, f: impl FnOnce(&mut Self) -> crate::Result<T>) -> crate::Result<T> {
+        self.cursor = 0;
+        let result = f(self);
+        match result {
+            Err(Error::Pending) => {}
+            Err(_) => self.lines.clear(),
+            Ok(_) => {
+                self.lines.drain(..self.cursor);
+            }
+        }
+        self.cursor = 0;
+        result
+    }
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for &mut CommandDeserializer<'de, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Custom(
+            "commands can only be deserialized into enums".to_string(),
+        ))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64
+        char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.command(|de| visitor.visit_enum(CommandAccess::new(de)))
+    }
+}
@@ -0,0 +1,447 @@
+use super::CommandDeserializer;
+use crate::{
+    deserializer::value::{Missing, ValueDeserializer},
+    read::Read,
+    Error,
+};
+use serde::{
+    de::{DeserializeSeed, EnumAccess, SeqAccess, VariantAccess, Visitor},
+    forward_to_deserialize_any, Deserializer,
+};
+use std::{borrow::Cow, slice};
+
+/// Names of the commands that are followed by a list of commands up to `command_list_end`.
+const COMMAND_LIST_BEGIN: [&[u8]; 2] = [b"command_list_begin", b"command_list_ok_begin"];
+
+/// Selects the variant by the name of the command at the cursor.
+pub(super) struct CommandAccess<'a, 'de, R> {
+    de: &'a mut CommandDeserializer<'de, R>,
+}
+
+impl<'a, 'de, R: Read<'de>> CommandAccess<'a, 'de, R> {
+    pub(super) fn new(de: &'a mut CommandDeserializer<'de, R>) -> Self {
+        CommandAccess { de }
+    }
+}
+
+impl<'a, 'de, R: Read<'de>> EnumAccess<'de> for CommandAccess<'a, 'de, R> {
+    type Error = Error;
+    type Variant = CommandVariant<'a, 'de, R>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name = &self.de.peek_line()?[0];
+        let value = seed.deserialize(ValueDeserializer::new(name))?;
+        let line = self.de.cursor;
+        self.de.cursor += 1;
+        Ok((value, CommandVariant { de: self.de, line }))
+    }
+}
+
+/// Deserializes the arguments of the command in `line` into the content of the variant.
+pub(super) struct CommandVariant<'a, 'de, R> {
+    de: &'a mut CommandDeserializer<'de, R>,
+    line: usize,
+}
+
+impl<'de, R: Read<'de>> CommandVariant<'_, 'de, R> {
+    fn arguments(&self) -> ArgumentsDeserializer<'_, 'de> {
+        ArgumentsDeserializer {
+            arguments: &self.de.lines[self.line][1..],
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> VariantAccess<'de> for CommandVariant<'_, 'de, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        serde::de::Deserialize::deserialize(self.arguments())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let name = &self.de.lines[self.line][0];
+        if COMMAND_LIST_BEGIN.contains(&&**name) {
+            let () = serde::de::Deserialize::deserialize(self.arguments())?;
+            seed.deserialize(CommandList { de: self.de })
+        } else {
+            seed.deserialize(self.arguments())
+        }
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.arguments().deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.arguments().deserialize_struct("", fields, visitor)
+    }
+}
+
+/// The commands following `command_list_begin`, up to `command_list_end`.
+struct CommandList<'a, 'de, R> {
+    de: &'a mut CommandDeserializer<'de, R>,
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for CommandList<'_, 'de, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64
+        char str string bytes byte_buf option
+        unit unit_struct newtype_struct
+        tuple tuple_struct map struct enum identifier ignored_any
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+}
+
+impl<'de, R: Read<'de>> SeqAccess<'de> for CommandList<'_, 'de, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let [name] = self.de.peek_line()? {
+            if **name == *b"command_list_end" {
+                self.de.cursor += 1;
+                return Ok(None);
+            }
+        }
+        seed.deserialize(CommandElement { de: self.de }).map(Some)
+    }
+}
+
+/// A single command inside of a command list.
+struct CommandElement<'a, 'de, R> {
+    de: &'a mut CommandDeserializer<'de, R>,
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for CommandElement<'_, 'de, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Custom(
+            "commands can only be deserialized into enums".to_string(),
+        ))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64
+        char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(CommandAccess { de: self.de })
+    }
+}
+
+/// Deserializes all arguments of a command.
+///
+/// Sequences, tuples and structs take the arguments positionally, everything else requires exactly
+/// one argument.
+struct ArgumentsDeserializer<'a, 'de> {
+    arguments: &'a [Cow<'de, [u8]>],
+}
+
+impl<'a, 'de> ArgumentsDeserializer<'a, 'de> {
+    fn single(&self) -> crate::Result<ValueDeserializer<'a, 'de>> {
+        match self.arguments {
+            [argument] => Ok(ValueDeserializer::new(argument)),
+            arguments => Err(serde::de::Error::invalid_length(
+                arguments.len(),
+                &"a single argument",
+            )),
+        }
+    }
+
+    fn visit_arguments<V>(self, len: Option<usize>, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut arguments = Arguments {
+            arguments: self.arguments.iter(),
+            len,
+        };
+        let value = visitor.visit_seq(&mut arguments)?;
+        match arguments.arguments.len() {
+            0 => Ok(value),
+            left => Err(Error::Custom(format!(
+                "expected {} arguments, found {}",
+                self.arguments.len() - left,
+                self.arguments.len()
+            ))),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.single()?.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ArgumentsDeserializer<'_, 'de> {
+    type Error = Error;
+
+    forward_to_single! {
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char()
+        deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.arguments {
+            [] => visitor.visit_unit(),
+            [argument] => ValueDeserializer::new(argument).deserialize_any(visitor),
+            _ => self.visit_arguments(None, visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.arguments.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.arguments.len() {
+            0 => visitor.visit_unit(),
+            len => Err(serde::de::Error::invalid_length(len, &"no arguments")),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_arguments(None, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_arguments(Some(len), visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_arguments(Some(len), visitor)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Custom(
+            "arguments can't be deserialized as a map".to_string(),
+        ))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_arguments(Some(fields.len()), visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+/// The arguments of a command as a sequence.
+///
+/// If the length is known, arguments missing at the end are deserialized as [`Missing`], so
+/// trailing optional arguments can be left out.
+struct Arguments<'a, 'de> {
+    arguments: slice::Iter<'a, Cow<'de, [u8]>>,
+    len: Option<usize>,
+}
+
+impl<'de> SeqAccess<'de> for Arguments<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match &mut self.len {
+            Some(0) => return Ok(None),
+            Some(len) => {
+                *len -= 1;
+                if self.arguments.len() == 0 {
+                    return seed.deserialize(Missing).map(Some);
+                }
+            }
+            None if self.arguments.len() == 0 => return Ok(None),
+            None => {}
+        }
+        seed.deserialize(ArgumentDeserializer {
+            arguments: &mut self.arguments,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.len.or(Some(self.arguments.len()))
+    }
+}
+
+/// Deserializes the next argument, or all remaining ones as a sequence.
+struct ArgumentDeserializer<'b, 'a, 'de> {
+    arguments: &'b mut slice::Iter<'a, Cow<'de, [u8]>>,
+}
+
+impl<'a, 'de> ArgumentDeserializer<'_, 'a, 'de> {
+    fn next(self) -> ValueDeserializer<'a, 'de> {
+        ValueDeserializer::new(
+            self.arguments
+                .next()
+                .expect("expected at least one argument to be left"),
+        )
+    }
+}
+
+macro_rules! forward_to_next {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.next().$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ArgumentDeserializer<'_, '_, 'de> {
+    type Error = Error;
+
+    forward_to_next! {
+        deserialize_any()
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char()
+        deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+        deserialize_option() deserialize_unit() deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier() deserialize_ignored_any()
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(Arguments {
+            arguments: std::mem::replace(self.arguments, [].iter()),
+            len: None,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    read::{Reference, SliceDebug},
+    Error, Result,
+};
+use std::borrow::Cow;
+
+/// Splits a command line (without its trailing newline) into its arguments, the first one being
+/// the name of the command.
+///
+/// Arguments are separated by whitespace, unless they are quoted. Inside of quotes every character
+/// following a backslash is taken literally.
+pub(super) fn split<'de>(line: Reference<'de, '_, [u8]>) -> Result<Vec<Cow<'de, [u8]>>> {
+    match line {
+        Reference::Borrowed(line) => split_with(line, Cow::Borrowed),
+        Reference::Copied(line) => split_with(line, |bytes| Cow::Owned(bytes.to_vec())),
+    }
+}
+
+fn split_with<'a, 'de>(
+    mut line: &'a [u8],
+    cow: impl Fn(&'a [u8]) -> Cow<'de, [u8]>,
+) -> Result<Vec<Cow<'de, [u8]>>> {
+    let is_space = |byte: &u8| matches!(byte, b' ' | b'\t' | b'\r');
+    let mut arguments = Vec::new();
+
+    loop {
+        let start = line.iter().position(|byte| !is_space(byte));
+        line = &line[start.unwrap_or(line.len())..];
+
+        match line {
+            [] => break Ok(arguments),
+            [b'"', quoted @ ..] => {
+                let mut escaped = false;
+                let mut index = 0;
+                while let Some(byte) = quoted.get(index) {
+                    match byte {
+                        b'"' => break,
+                        b'\\' => {
+                            escaped = true;
+                            index += 2;
+                        }
+                        _ => index += 1,
+                    }
+                }
+
+                let (Some(argument), Some(rest)) = (quoted.get(..index), quoted.get(index + 1..))
+                else {
+                    return Err(Error::Syntax(format!(
+                        "missing closing quote in {:?}",
+                        SliceDebug(line)
+                    )));
+                };
+                if rest.first().is_some_and(|byte| !is_space(byte)) {
+                    return Err(Error::Syntax(format!(
+                        "expected whitespace after closing quote, found {:?}",
+                        SliceDebug(rest)
+                    )));
+                }
+
+                arguments.push(if escaped {
+                    Cow::Owned(unescape(argument))
+                } else {
+                    cow(argument)
+                });
+                line = rest;
+            }
+            _ => {
+                let end = line.iter().position(is_space).unwrap_or(line.len());
+                arguments.push(cow(&line[..end]));
+                line = &line[end..];
+            }
+        }
+    }
+}
+
+fn unescape(argument: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(argument.len());
+    let mut bytes = argument.iter();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\\' => unescaped.extend(bytes.next()),
+            byte => unescaped.push(*byte),
+        }
+    }
+    unescaped
+}
@@ -0,0 +1,159 @@
+use crate::{
+    read::{InteruptSlice, Slice},
+    CommandDeserializer, Error,
+};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Command<'a> {
+    Status,
+    Play(Option<u32>),
+    Find(
+        #[serde(borrow)] Cow<'a, str>,
+        Option<&'a str>,
+        Option<(u32, Option<u32>)>,
+    ),
+    Add {
+        uri: &'a str,
+        position: Option<i32>,
+    },
+    Binarylimit(u64),
+    CommandListBegin(Vec<Command<'a>>),
+    CommandListOkBegin(Vec<Command<'a>>),
+}
+
+fn deserialize(src: &[u8]) -> Result<Vec<Command<'_>>, Error> {
+    let mut de = CommandDeserializer::from_read(Slice { src });
+    let mut commands = Vec::new();
+    while !de.read.src.is_empty() {
+        commands.push(Command::deserialize(&mut de)?);
+    }
+    Ok(commands)
+}
+
+#[test]
+fn find_with_escaped_filter() {
+    let commands =
+        deserialize(b"find \"(Artist == \\\"x\\\")\" window 0:5\nfind \"(Album == y)\"\n");
+    assert_eq!(
+        commands,
+        Ok(vec![
+            Command::Find(
+                Cow::Owned("(Artist == \"x\")".to_string()),
+                Some("window"),
+                Some((0, Some(5))),
+            ),
+            Command::Find(Cow::Borrowed("(Album == y)"), None, None),
+        ])
+    );
+}
+
+#[test]
+fn optional_arguments() {
+    let commands = deserialize(b"status\nplay\nplay 3\nadd a.flac\nadd \"b c.flac\" -1\n");
+    assert_eq!(
+        commands,
+        Ok(vec![
+            Command::Status,
+            Command::Play(None),
+            Command::Play(Some(3)),
+            Command::Add {
+                uri: "a.flac",
+                position: None,
+            },
+            Command::Add {
+                uri: "b c.flac",
+                position: Some(-1),
+            },
+        ])
+    );
+}
+
+#[test]
+fn binarylimit() {
+    assert_eq!(
+        deserialize(b"binarylimit 8192\n"),
+        Ok(vec![Command::Binarylimit(8192)])
+    );
+}
+
+#[test]
+fn command_list() {
+    let commands = deserialize(
+        b"command_list_ok_begin\nplay 1\nstatus\ncommand_list_end\ncommand_list_begin\ncommand_list_end\n",
+    );
+    assert_eq!(
+        commands,
+        Ok(vec![
+            Command::CommandListOkBegin(vec![Command::Play(Some(1)), Command::Status]),
+            Command::CommandListBegin(vec![]),
+        ])
+    );
+}
+
+#[test]
+fn command_list_interrupted() {
+    let src: &mut [&[u8]] = &mut [
+        b"command_list_begin\nfind \"(Artist == \\\"",
+        b"x\\\")\"\nadd a.flac\n",
+        b"command_list_end\n",
+    ];
+    let mut de = CommandDeserializer::from_read(InteruptSlice { src });
+
+    assert_eq!(Command::deserialize(&mut de), Err(Error::Pending));
+    assert_eq!(de.lines.len(), 1);
+    assert_eq!(Command::deserialize(&mut de), Err(Error::Pending));
+    assert_eq!(de.lines.len(), 3);
+    assert_eq!(
+        Command::deserialize(&mut de),
+        Ok(Command::CommandListBegin(vec![
+            Command::Find(Cow::Owned("(Artist == \"x\")".to_string()), None, None),
+            Command::Add {
+                uri: "a.flac",
+                position: None,
+            },
+        ]))
+    );
+    assert!(de.lines.is_empty());
+}
+
+#[test]
+fn invalid_arguments() {
+    assert_eq!(
+        deserialize(b"status 1\n"),
+        Err(Error::Custom(
+            "invalid length 1, expected no arguments".to_string()
+        ))
+    );
+    assert_eq!(
+        deserialize(b"play 1 2\n"),
+        Err(Error::Custom(
+            "invalid length 2, expected a single argument".to_string()
+        ))
+    );
+    assert_eq!(
+        deserialize(b"find \"(Artist == x)\n"),
+        Err(Error::Syntax(
+            r#"missing closing quote in b"\"(Artist == x)""#.to_string()
+        ))
+    );
+    assert_eq!(
+        deserialize(b"add\n"),
+        Err(Error::Custom("missing value".to_string()))
+    );
+}
+
+#[test]
+fn recovers_after_invalid_command() {
+    let mut de = CommandDeserializer::from_read(Slice {
+        src: b"bogus\nplay \"1\nplay 1 2\nstatus\n",
+    });
+    assert!(Command::deserialize(&mut de).is_err());
+    assert!(Command::deserialize(&mut de).is_err());
+    assert!(Command::deserialize(&mut de).is_err());
+    assert_eq!(Command::deserialize(&mut de), Ok(Command::Status));
+    assert!(de.lines.is_empty());
+}
@@ -1,88 +1,519 @@
-use crate::{read::Read, Error};
-use serde::{de::Visitor, forward_to_deserialize_any, Deserializer};
+use self::{
+    access::{PairDeserializer, RecordAccess, Records},
+    token::{Pair, Token},
+};
+use crate::{
+    lexer::Lexer,
+    read::{Read, Slice},
+    value::{Field, Value},
+    Error, Tag, Version,
+};
+use serde::{de::Visitor, forward_to_deserialize_any, Deserialize, Deserializer};
+use std::borrow::Cow;
+
+mod access;
+mod token;
 
 #[cfg(test)]
 mod tests;
 
+/// Deserializes the responses the server sends after receiving a request.
+///
+/// For the requests (commands) themselves see [`CommandDeserializer`](crate::CommandDeserializer).
+///
+/// Keys and values that are contiguous in the input are visited as borrowed, values that had to
+/// be copied into the scratch buffer (because they were split across multiple chunks) are not.
+/// Fields that should borrow whenever possible, but still work with any input, can use
+/// `#[serde(borrow)] Cow<'de, str>` or `#[serde(borrow)] Cow<'de, [u8]>`.
+///
+/// If deserialization is interrupted by [`Error::Pending`], the lines of the response that were
+/// already read are kept, so the deserialization can be retried once more input is available.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
-pub struct RequestDeserializer<R> {
-    read: R,
-    scratch: Vec<u8>,
-    state: DeserializerState,
+pub struct RequestDeserializer<'de, R> {
+    lexer: Lexer<R>,
+    /// Tokens of the response that is currently being deserialized.
+    tokens: Vec<Token<'de>>,
+    /// Index of the next token in `tokens` that hasn't been visited yet.
+    cursor: usize,
+    /// See [`RequestDeserializer::normalize_keys`].
+    normalize_keys: bool,
+    /// See [`RequestDeserializer::version`].
+    version: Option<Version>,
+    /// See [`RequestDeserializer::lenient`].
+    lenient: bool,
+    /// See [`RequestDeserializer::tags`].
+    tags: Option<Vec<Tag>>,
+    /// Index of the first token in `tokens` whose value failed to deserialize.
+    invalid: Option<usize>,
+    warnings: Vec<Warning>,
+    /// Whether deserializing the last response failed before it was consumed completely.
+    incomplete: bool,
 }
 
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
-enum DeserializerState {
-    #[default]
-    None,
-    IgnoreAnyAck,
+/// A pair that was skipped in lenient mode, because its value failed to deserialize.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Warning {
+    pub key: String,
+    pub message: String,
 }
 
-impl<'de, R: Read<'de>> RequestDeserializer<R> {
+impl<'de, R: Read<'de>> RequestDeserializer<'de, R> {
     pub fn from_read(read: R) -> Self {
         RequestDeserializer {
-            read,
-            scratch: Vec::new(),
-            state: DeserializerState::None,
+            lexer: Lexer::from_read(read),
+            tokens: Vec::new(),
+            cursor: 0,
+            normalize_keys: false,
+            version: None,
+            lenient: false,
+            tags: None,
+            invalid: None,
+            warnings: Vec::new(),
+            incomplete: false,
         }
     }
+
+    pub fn into_inner(self) -> R {
+        self.lexer.into_inner()
+    }
+
+    /// Sets whether keys are matched to struct fields ignoring ASCII case and treating `-` and
+    /// `_` as equal, so `artist`, `Artist` and `ARTIST` all match a field named `Artist` and
+    /// `Last-Modified` matches `last_modified`.
+    ///
+    /// Only applies to structs, not to maps or flattened fields.
+    pub fn normalize_keys(mut self, normalize: bool) -> Self {
+        self.normalize_keys = normalize;
+        self
+    }
+
+    /// Sets the protocol version of the server, which decides whether fields annotated with a
+    /// version (see [`Version`]) are required.
+    pub fn version(mut self, version: Option<Version>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets whether responses are deserialized leniently.
+    ///
+    /// When deserializing with [`RequestDeserializer::deserialize`] in lenient mode, pairs whose
+    /// values fail to deserialize are skipped as if they were missing, so `Option` fields become
+    /// `None` and fields with `#[serde(default)]` their default, and a [`Warning`] is recorded for
+    /// each of them. If deserializing a response fails nonetheless, the rest of it is still
+    /// consumed, so the next response can be deserialized.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets the tags the server sends, like the ones enabled with `tagtypes`, or all if `None`.
+    ///
+    /// Pairs of other tags are skipped, so a [`Song`](crate::model::Song) only ever contains the
+    /// negotiated tags, even if the server sends more. Keys that aren't known tags are kept.
+    pub fn tags(mut self, tags: Option<Vec<Tag>>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Returns the warnings recorded in lenient mode.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Skips the rest of a response that failed to deserialize, up to and including the `OK` or
+    /// `ACK` terminating it, so the next response can be deserialized.
+    ///
+    /// Lines that can't be parsed are skipped as well, binary data is skipped by its announced
+    /// length. Does nothing if the last response was consumed completely. If interrupted by
+    /// [`Error::Pending`], it can be called again once more input is available.
+    pub fn recover(&mut self) -> crate::Result<()> {
+        while self.incomplete {
+            let end = self
+                .tokens
+                .iter()
+                .position(|token| matches!(token, Token::Ok | Token::Ack(_)));
+            match end {
+                Some(index) => {
+                    self.tokens.drain(..=index);
+                    self.incomplete = false;
+                }
+                None => {
+                    self.tokens.clear();
+                    match self.lexer.next_event() {
+                        Ok(event) => {
+                            let token = Token::from(event);
+                            self.lexer.release();
+                            self.tokens.push(token);
+                        }
+                        Err(error) if error.is_syntax() => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes the next response as `T`, skipping invalid pairs in lenient mode.
+    pub fn deserialize<T: Deserialize<'de>>(&mut self) -> crate::Result<T> {
+        if !self.lenient {
+            return T::deserialize(self);
+        }
+        self.response(|de| loop {
+            de.cursor = 0;
+            de.invalid = None;
+            match T::deserialize(Response { de: &mut *de }) {
+                Err(Error::Custom(message)) if de.invalid.is_some() => {
+                    let index = de.invalid.take().expect("expected an invalid token");
+                    let (key, _) = de.tokens[index]
+                        .pair()
+                        .expect("expected the invalid token to be a pair");
+                    de.warnings.push(Warning {
+                        key: String::from_utf8_lossy(key).into_owned(),
+                        message,
+                    });
+                    de.tokens.remove(index);
+                }
+                result => break result,
+            }
+        })
+    }
+}
+
+impl<'de> RequestDeserializer<'de, Slice<'de>> {
+    /// Returns a deserializer over the pairs of `value`, followed by an `OK`.
+    pub(crate) fn from_value(value: &'de Value) -> Self {
+        let tokens = value
+            .0
+            .iter()
+            .flat_map(|record| &record.0)
+            .map(|(key, field)| match field {
+                Field::Text(text) => Token::Pair {
+                    key: Cow::Borrowed(key.as_bytes()),
+                    value: Cow::Borrowed(text.as_bytes()),
+                },
+                Field::Binary(data) => Token::Binary(Cow::Borrowed(data)),
+            })
+            .chain([Token::Ok])
+            .collect();
+        RequestDeserializer {
+            tokens,
+            ..RequestDeserializer::from_read(Slice { src: &[] })
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> RequestDeserializer<'de, R> {
+    fn peek_token(&mut self) -> crate::Result<&Token<'de>> {
+        while self.cursor == self.tokens.len() {
+            let token = Token::from(self.lexer.next_event()?);
+            self.lexer.release();
+            if !self.is_skipped_tag(&token) {
+                self.tokens.push(token);
+            }
+        }
+        Ok(&self.tokens[self.cursor])
+    }
+
+    /// Whether `token` is a pair of a known tag that isn't in [`RequestDeserializer::tags`].
+    fn is_skipped_tag(&self, token: &Token<'de>) -> bool {
+        let (Some(tags), Token::Pair { key, .. }) = (&self.tags, token) else {
+            return false;
+        };
+        let Ok(key) = std::str::from_utf8(key) else {
+            return false;
+        };
+        let Ok(tag) = key.parse::<Tag>();
+        !matches!(tag, Tag::Other(_)) && !tags.contains(&tag)
+    }
+
+    /// Returns the key and value of the pair at the cursor.
+    fn pair(&self) -> Pair<'_, 'de> {
+        self.tokens[self.cursor]
+            .pair()
+            .expect("expected the token at the cursor to be a pair")
+    }
+
+    /// Consumes the `ACK` token at the cursor and turns it into an error.
+    fn ack(&mut self) -> Error {
+        let Token::Ack(ack) = &self.tokens[self.cursor] else {
+            unreachable!("expected the token at the cursor to be an ack");
+        };
+        let error = Error::Ack(ack.clone());
+        self.cursor += 1;
+        error
+    }
+
+    /// Remembers the pair at the cursor as invalid if deserializing its value failed.
+    fn check_value<T>(&mut self, result: crate::Result<T>) -> crate::Result<T> {
+        if let Err(Error::Custom(_)) = result {
+            self.invalid.get_or_insert(self.cursor);
+        }
+        result
+    }
+
+    /// Consumes the rest of the response, including the `OK`, `list_OK` or `ACK` terminating it.
+    fn skip_response(&mut self) -> crate::Result<()> {
+        loop {
+            let end = matches!(
+                self.peek_token()?,
+                Token::Ok | Token::ListOk | Token::Ack(_)
+            );
+            self.cursor += 1;
+            if end {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consumes the `OK` or `list_OK` that terminates a response.
+    fn end(&mut self) -> crate::Result<()> {
+        match self.peek_token()? {
+            Token::Ok | Token::ListOk => {
+                self.cursor += 1;
+                Ok(())
+            }
+            Token::Ack(_) => Err(self.ack()),
+            Token::Pair { key, .. } => Err(Error::Custom(format!(
+                "expected end of response, found key {:?}",
+                String::from_utf8_lossy(key)
+            ))),
+            Token::Binary(_) => Err(Error::Custom(
+                "expected end of response, found binary data".to_string(),
+            )),
+        }
+    }
+
+    /// Whether the first key of the response at the cursor occurs again, making it a list of
+    /// records.
+    fn is_list(&mut self) -> crate::Result<bool> {
+        let start = self.cursor;
+        let mut delimiter = None;
+        let result = loop {
+            match self.peek_token() {
+                Ok(Token::Ok | Token::ListOk | Token::Ack(_)) => break Ok(false),
+                Ok(token) => {
+                    let (key, _) = token.pair().expect("expected the token to be a pair");
+                    match &delimiter {
+                        None => delimiter = Some(key.clone()),
+                        Some(delimiter) if delimiter == key => break Ok(true),
+                        Some(_) => {}
+                    }
+                }
+                Err(error) => break Err(error),
+            }
+            self.cursor += 1;
+        };
+        self.cursor = start;
+        result
+    }
+
+    /// Runs `f` from the start of the buffered tokens.
+    ///
+    /// Unless `f` returned [`Error::Pending`], all tokens it consumed are discarded afterwards. In
+    /// lenient mode, the rest of the response is discarded as well if `f` failed.
+    fn response<T>(&mut self, f: impl FnOnce(&mut Self) -> crate::Result<T>) -> crate::Result<T> {
+        self.cursor = 0;
+        let mut result = f(self);
+        if self.lenient && matches!(&result, Err(error) if error.is_data() || error.is_syntax()) {
+            if let Err(Error::Pending) = self.skip_response() {
+                result = Err(Error::Pending);
+            }
+        }
+        if !matches!(result, Err(Error::Pending)) {
+            let end = self.cursor.checked_sub(1).map(|index| &self.tokens[index]);
+            self.incomplete =
+                result.is_err() && !matches!(end, Some(Token::Ok | Token::ListOk | Token::Ack(_)));
+            self.tokens.drain(..self.cursor);
+        }
+        self.cursor = 0;
+        result
+    }
+}
+
+macro_rules! forward_to_response {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.response(|de| Response { de }.$method($($arg,)* visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for &mut RequestDeserializer<'de, R> {
+    type Error = Error;
+
+    forward_to_response! {
+        deserialize_any()
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char()
+        deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+        deserialize_option() deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq() deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_ignored_any()
+    }
+
+    forward_to_deserialize_any! {
+        tuple tuple_struct identifier
+    }
+}
+
+/// Deserializes a whole response, including the `OK` terminating it.
+struct Response<'a, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
 }
 
-impl<'de, R: Read<'de>> Deserializer<'de> for &mut RequestDeserializer<R> {
+macro_rules! forward_to_pair {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = PairDeserializer::new(self.de)?.$method(visitor)?;
+                self.de.end()?;
+                Ok(value)
+            }
+        )*
+    };
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for Response<'_, 'de, R> {
     type Error = Error;
 
+    /// Deserializes a list of records if the first key occurs more than once, a single record
+    /// otherwise.
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_enum("", &[], visitor)
+        if self.de.is_list()? {
+            self.deserialize_seq(visitor)
+        } else {
+            self.deserialize_map(visitor)
+        }
+    }
+
+    forward_to_pair! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64
-        char str string bytes byte_buf option
-        unit unit_struct newtype_struct seq
-        tuple tuple_struct map struct identifier
+        tuple tuple_struct identifier
     }
 
-    fn deserialize_enum<V>(
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.de.peek_token()? {
+            Token::Ok | Token::ListOk => {
+                self.de.cursor += 1;
+                visitor.visit_none()
+            }
+            Token::Ack(_) => Err(self.de.ack()),
+            Token::Pair { .. } | Token::Binary(_) => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.de.end()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
         self,
         _: &'static str,
-        _: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_unit(visitor)
     }
 
-    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self.state {
-            DeserializerState::None => {
-                if self.read.starts_with(&mut self.scratch, b"ACK ")? {
-                    self.state = DeserializerState::IgnoreAnyAck;
-                    self.read.skip_until(b'\n')?;
-                    self.state = DeserializerState::None;
-                    return visitor.visit_unit();
-                }
+        visitor.visit_newtype_struct(self)
+    }
 
-                loop {
-                    if self.read.starts_with(&mut self.scratch, b"OK\n")? {
-                        break visitor.visit_unit();
-                    } else {
-                        self.read.skip_until(b'\n')?;
-                    }
-                }
-            }
-            DeserializerState::IgnoreAnyAck => {
-                self.read.skip_until(b'\n')?;
-                self.state = DeserializerState::None;
-                visitor.visit_unit()
-            }
-        }
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_seq(Records::new(&mut *self.de))?;
+        self.de.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_map(RecordAccess::new(&mut *self.de, None, &[]))?;
+        self.de.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_map(RecordAccess::new(&mut *self.de, None, fields))?;
+        self.de.end()?;
+        Ok(value)
+    }
+
+    /// Deserializes a unit variant from the value of a response consisting of a single pair.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = PairDeserializer::new(self.de)?.deserialize_enum(name, variants, visitor)?;
+        self.de.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.de.skip_response()?;
+        visitor.visit_unit()
     }
 }
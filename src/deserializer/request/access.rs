@@ -0,0 +1,390 @@
+use super::{token::Token, RequestDeserializer};
+use crate::{deserializer::value::ValueDeserializer, read::Read, version::split_field, Error};
+use serde::{
+    de::{
+        value::{BorrowedStrDeserializer, SeqDeserializer},
+        DeserializeSeed, MapAccess, SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+use std::borrow::Cow;
+
+/// Deserializes the value of the `key: value` pair at the cursor.
+///
+/// Sequences are made up of the values of the pair and all directly following pairs with the same
+/// key, everything else is deserialized from the value of the pair alone.
+pub(super) struct PairDeserializer<'a, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
+}
+
+impl<'a, 'de, R: Read<'de>> PairDeserializer<'a, 'de, R> {
+    pub(super) fn new(de: &'a mut RequestDeserializer<'de, R>) -> crate::Result<Self> {
+        match de.peek_token()? {
+            Token::Pair { .. } | Token::Binary(_) => Ok(PairDeserializer { de }),
+            Token::Ack(_) => Err(de.ack()),
+            Token::Ok | Token::ListOk => Err(Error::Custom(
+                "expected `key: value`, found end of response".to_string(),
+            )),
+        }
+    }
+
+    fn value(&self) -> ValueDeserializer<'_, 'de> {
+        ValueDeserializer::new(self.de.pair().1)
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.value().$method($($arg,)* visitor);
+                let value = self.de.check_value(value)?;
+                self.de.cursor += 1;
+                Ok(value)
+            }
+        )*
+    };
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for PairDeserializer<'_, 'de, R> {
+    type Error = Error;
+
+    forward_to_value! {
+        deserialize_any()
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char()
+        deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+        deserialize_unit() deserialize_unit_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier() deserialize_ignored_any()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Token::Binary(data) = &self.de.tokens[self.de.cursor] {
+            let value = visitor.visit_seq(SeqDeserializer::new(data.iter().copied()));
+            let value = self.de.check_value(value)?;
+            self.de.cursor += 1;
+            return Ok(value);
+        }
+
+        let key = self.de.pair().0.clone();
+        visitor.visit_seq(RepeatedValues {
+            de: self.de,
+            key,
+            first: true,
+        })
+    }
+}
+
+/// The values of consecutive pairs sharing the same key.
+struct RepeatedValues<'a, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
+    key: Cow<'de, [u8]>,
+    first: bool,
+}
+
+impl<'de, R: Read<'de>> SeqAccess<'de> for RepeatedValues<'_, 'de, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.first {
+            match self.de.peek_token()? {
+                token if token.pair().is_some_and(|(key, _)| *key == self.key) => {}
+                _ => return Ok(None),
+            }
+        }
+        self.first = false;
+
+        let value = seed.deserialize(ValueDeserializer::new(self.de.pair().1));
+        let value = self.de.check_value(value)?;
+        self.de.cursor += 1;
+        Ok(Some(value))
+    }
+}
+
+/// The pairs of a single record.
+///
+/// A record ends at the end of the response or, if given, once the `delimiter` key is encountered
+/// again.
+///
+/// If the deserializer normalizes keys, the delimiter is compared after normalization and keys
+/// matching one of `fields` after normalization are visited as that field. Keys of fields
+/// annotated with a version are visited as the annotated field.
+pub(super) struct RecordAccess<'a, 'b, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
+    delimiter: Option<&'b Cow<'de, [u8]>>,
+    fields: &'static [&'static str],
+    /// Fields that were visited by a name other than their key.
+    seen: Vec<&'static str>,
+    first: bool,
+}
+
+impl<'a, 'b, 'de, R: Read<'de>> RecordAccess<'a, 'b, 'de, R> {
+    pub(super) fn new(
+        de: &'a mut RequestDeserializer<'de, R>,
+        delimiter: Option<&'b Cow<'de, [u8]>>,
+        fields: &'static [&'static str],
+    ) -> Self {
+        RecordAccess {
+            de,
+            delimiter,
+            fields,
+            seen: Vec::new(),
+            first: true,
+        }
+    }
+
+    /// Fails if a field annotated with a version the server has is missing, see [`Version`](crate::Version).
+    fn check_missing(&self) -> crate::Result<()> {
+        let Some(version) = self.de.version else {
+            return Ok(());
+        };
+        for field in self.fields {
+            if let (key, Some(since)) = split_field(field) {
+                if since <= version && !self.seen.contains(field) {
+                    return Err(Error::Custom(format!(
+                        "missing field `{key}`, which is sent since protocol version {since}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares keys ignoring ASCII case and treating `-` and `_` as equal.
+fn normalized_eq(a: &[u8], b: &[u8]) -> bool {
+    fn normalize(byte: &u8) -> u8 {
+        match byte {
+            b'-' => b'_',
+            byte => byte.to_ascii_lowercase(),
+        }
+    }
+    a.len() == b.len() && a.iter().map(normalize).eq(b.iter().map(normalize))
+}
+
+impl<'de, R: Read<'de>> MapAccess<'de> for RecordAccess<'_, '_, 'de, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let normalize_keys = self.de.normalize_keys;
+        let end = match self.de.peek_token()? {
+            Token::Ok | Token::ListOk => true,
+            Token::Ack(_) => return Err(self.de.ack()),
+            token @ (Token::Pair { .. } | Token::Binary(_)) => {
+                let (key, _) = token.pair().expect("expected the token to be a pair");
+                let delimiter = self.delimiter.is_some_and(|delimiter| {
+                    if normalize_keys {
+                        normalized_eq(delimiter, key)
+                    } else {
+                        delimiter == key
+                    }
+                });
+                !self.first && delimiter
+            }
+        };
+        if end {
+            self.check_missing()?;
+            return Ok(None);
+        }
+        self.first = false;
+
+        let (key, _) = self.de.pair();
+        let field = self.fields.iter().copied().find(|field| {
+            let (name, version) = split_field(field);
+            if normalize_keys {
+                normalized_eq(name.as_bytes(), key)
+            } else {
+                version.is_some() && name.as_bytes() == &**key
+            }
+        });
+        match field {
+            Some(field) => {
+                self.seen.push(field);
+                seed.deserialize(BorrowedStrDeserializer::new(field))
+                    .map(Some)
+            }
+            None => seed.deserialize(ValueDeserializer::new(key)).map(Some),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(PairDeserializer { de: self.de })
+    }
+}
+
+/// The records of a response containing a list of records.
+///
+/// The first key of the first record is taken as the delimiter separating the records.
+pub(super) struct Records<'a, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
+    delimiter: Option<Cow<'de, [u8]>>,
+}
+
+impl<'a, 'de, R: Read<'de>> Records<'a, 'de, R> {
+    pub(super) fn new(de: &'a mut RequestDeserializer<'de, R>) -> Self {
+        Records {
+            de,
+            delimiter: None,
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> SeqAccess<'de> for Records<'_, 'de, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.de.peek_token()? {
+            Token::Ok | Token::ListOk => return Ok(None),
+            Token::Ack(_) => return Err(self.de.ack()),
+            token @ (Token::Pair { .. } | Token::Binary(_)) => {
+                let (key, _) = token.pair().expect("expected the token to be a pair");
+                if self.delimiter.is_none() {
+                    self.delimiter = Some(key.clone());
+                }
+            }
+        }
+
+        seed.deserialize(RecordDeserializer {
+            de: self.de,
+            delimiter: self.delimiter.as_ref(),
+        })
+        .map(Some)
+    }
+}
+
+/// Deserializes a single record of a list of records.
+struct RecordDeserializer<'a, 'b, 'de, R> {
+    de: &'a mut RequestDeserializer<'de, R>,
+    delimiter: Option<&'b Cow<'de, [u8]>>,
+}
+
+macro_rules! forward_to_pair {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                PairDeserializer { de: self.de }.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de> for RecordDeserializer<'_, '_, 'de, R> {
+    type Error = Error;
+
+    forward_to_pair! {
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64() deserialize_char()
+        deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+        deserialize_unit() deserialize_unit_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+
+    forward_to_deserialize_any! {
+        tuple tuple_struct identifier
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(RecordAccess::new(self.de, self.delimiter, &[]))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(RecordAccess::new(self.de, self.delimiter, fields))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut record = RecordAccess::new(self.de, self.delimiter, &[]);
+        while record
+            .next_key_seed(std::marker::PhantomData::<serde::de::IgnoredAny>)?
+            .is_some()
+        {
+            record.de.cursor += 1;
+        }
+        visitor.visit_unit()
+    }
+}
@@ -1,2 +1,10 @@
+mod any;
+mod binary;
+mod borrowed;
+mod cow;
+mod enums;
 mod ignored_any;
+mod keys;
+mod lenient;
 mod macros;
+mod recover;
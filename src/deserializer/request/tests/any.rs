@@ -0,0 +1,96 @@
+use crate::{
+    deserializer::request::RequestDeserializer,
+    read::{InteruptSlice, Slice},
+    Ack, Error,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+fn map<const N: usize>(pairs: [(&str, &str); N]) -> BTreeMap<String, String> {
+    pairs
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+enum Any {
+    Record(BTreeMap<String, String>),
+    Records(Vec<BTreeMap<String, String>>),
+}
+
+#[test]
+fn record() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"volume: 50\nstate: play\nOK\n",
+    });
+    assert_eq!(
+        Any::deserialize(&mut de),
+        Ok(Any::Record(map([("volume", "50"), ("state", "play")])))
+    );
+}
+
+#[test]
+fn records() {
+    let src: &mut [&[u8]] = &mut [b"file: a\nTitle: A\nfi", b"le: b\nOK\n"];
+    let mut de = RequestDeserializer::from_read(InteruptSlice { src });
+    assert_eq!(Any::deserialize(&mut de), Err(Error::Pending));
+    assert_eq!(
+        Any::deserialize(&mut de),
+        Ok(Any::Records(vec![
+            map([("file", "a"), ("Title", "A")]),
+            map([("file", "b")]),
+        ]))
+    );
+}
+
+#[test]
+fn flatten() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Song {
+        file: String,
+        #[serde(flatten)]
+        extra: HashMap<String, String>,
+    }
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"file: a\nTitle: A\nLabel: L\nfile: b\nOK\n",
+    });
+    assert_eq!(
+        Vec::<Song>::deserialize(&mut de),
+        Ok(vec![
+            Song {
+                file: "a".to_string(),
+                extra: HashMap::from([
+                    ("Title".to_string(), "A".to_string()),
+                    ("Label".to_string(), "L".to_string()),
+                ]),
+            },
+            Song {
+                file: "b".to_string(),
+                extra: HashMap::new(),
+            },
+        ])
+    );
+}
+
+#[test]
+fn ack() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"ACK [50@0] {find} no such song\nOK\n",
+    });
+    assert_eq!(
+        HashMap::<String, String>::deserialize(&mut de),
+        Err(Error::Ack(Ack {
+            code: 50,
+            index: 0,
+            command: "find".to_string(),
+            message: "no such song".to_string(),
+        }))
+    );
+    assert_eq!(
+        HashMap::<String, String>::deserialize(&mut de),
+        Ok(HashMap::new())
+    );
+}
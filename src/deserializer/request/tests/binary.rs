@@ -0,0 +1,72 @@
+use crate::{
+    deserializer::request::{tests::macros::test_deserialize, RequestDeserializer},
+    lexer::LexerState,
+    read::{InteruptSlice, Slice},
+    Error::Pending,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Picture<'a> {
+    size: u32,
+    binary: &'a [u8],
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct OwnedPicture {
+    size: u32,
+    binary: Vec<u8>,
+}
+
+test_deserialize! {
+    binary;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"size: 4\nbinary: 4\n\nOK\n\nOK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok(Picture {
+        size: 4,
+        binary: b"\nOK\n",
+    });
+}
+test_deserialize! {
+    binary_interrupt_data;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"size: 4\nbinary: 4\n\x89P", b"NG\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"NG\nOK\n"];
+    b"\x89P";
+    LexerState::Binary(2);
+    Err(Pending);
+
+    &[b""];
+    b"";
+    LexerState::Line;
+    Ok(OwnedPicture {
+        size: 4,
+        binary: b"\x89PNG".to_vec(),
+    });
+}
+test_deserialize! {
+    binary_interrupt_newline;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"size: 4\nbinary: 4\n\x89PNG", b"\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"\nOK\n"];
+    b"";
+    LexerState::BinaryEnd;
+    Err(Pending);
+
+    &[b""];
+    b"";
+    LexerState::Line;
+    Ok(Picture {
+        size: 4,
+        binary: b"\x89PNG",
+    });
+}
@@ -0,0 +1,141 @@
+use crate::{
+    deserializer::request::{tests::macros::test_deserialize, RequestDeserializer},
+    lexer::LexerState,
+    read::{InteruptSlice, Slice},
+    Ack, Error,
+    Error::Pending,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Song<'a> {
+    file: &'a str,
+    #[serde(rename = "Title")]
+    title: Option<&'a str>,
+    #[serde(rename = "Artist", default)]
+    artists: Vec<&'a str>,
+    #[serde(rename = "Pos")]
+    pos: Option<u32>,
+}
+
+test_deserialize! {
+    borrowed_struct;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"file: a.flac\nTitle: A\nArtist: B\nArtist: C\nPos: 3\nOK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok(Song {
+        file: "a.flac",
+        title: Some("A"),
+        artists: vec!["B", "C"],
+        pos: Some(3),
+    });
+}
+test_deserialize! {
+    borrowed_bytes;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"key: \xff\xfe\nOK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok::<&[u8], Error>(b"\xff\xfe");
+}
+test_deserialize! {
+    borrowed_records;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"file: a.flac\nTitle: A\nfile: b.flac\nArtist: B\nOK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok(vec![
+        Song {
+            file: "a.flac",
+            title: Some("A"),
+            artists: vec![],
+            pos: None,
+        },
+        Song {
+            file: "b.flac",
+            title: None,
+            artists: vec!["B"],
+            pos: None,
+        },
+    ]);
+}
+test_deserialize! {
+    borrowed_empty;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"OK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok::<Option<Song>, Error>(None);
+}
+test_deserialize! {
+    borrowed_ack;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"ACK [50@0] {playlistinfo} Bad song index\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Err::<Song, Error>(Error::Ack(Ack {
+        code: 50,
+        index: 0,
+        command: "playlistinfo".to_string(),
+        message: "Bad song index".to_string(),
+    }));
+}
+
+test_deserialize! {
+    borrowed_interrupt_between_lines;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"file: a.flac\n", b"Title: A\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"Title: A\nOK\n"];
+    b"";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b""];
+    b"";
+    LexerState::Line;
+    Ok(Song {
+        file: "a.flac",
+        title: Some("A"),
+        artists: vec![],
+        pos: None,
+    });
+}
+test_deserialize! {
+    borrowed_interrupt_value;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"file: a.flac\nTitle: ", b"A\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"A\nOK\n"];
+    b"Title: ";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b"OK\n"];
+    b"";
+    LexerState::Line;
+    Err::<Song, Error>(Error::Custom(
+        "invalid type: string \"A\", expected a borrowed string; the value was split across \
+         chunks of the input and had to be copied, so it can't be borrowed \
+         (use `String` or `Cow<'de, str>` instead)"
+            .to_string(),
+    ));
+}
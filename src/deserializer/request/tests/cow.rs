@@ -0,0 +1,129 @@
+use crate::{
+    deserializer::request::{tests::macros::test_deserialize, RequestDeserializer},
+    lexer::LexerState,
+    read::{InteruptSlice, Slice},
+    Error,
+    Error::Pending,
+};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+macro_rules! exact {
+    ($($name:ident($ty:ty) => $borrowed:ident, $owned:ident;)*) => {
+        $(
+            /// Compares equal only if both the value and the [`Cow`] variant match.
+            #[derive(Deserialize, Debug)]
+            #[serde(transparent)]
+            struct $name<'a>(#[serde(borrow)] Cow<'a, $ty>);
+
+            impl PartialEq for $name<'_> {
+                fn eq(&self, other: &Self) -> bool {
+                    matches!(
+                        (&self.0, &other.0),
+                        (Cow::Borrowed(_), Cow::Borrowed(_)) | (Cow::Owned(_), Cow::Owned(_))
+                    ) && self.0 == other.0
+                }
+            }
+
+            fn $borrowed(value: &$ty) -> $name<'_> {
+                $name(Cow::Borrowed(value))
+            }
+            fn $owned(value: &$ty) -> $name<'static> {
+                $name(Cow::Owned(value.to_owned()))
+            }
+        )*
+    };
+}
+
+exact! {
+    ExactStr(str) => borrowed, owned;
+    ExactBytes([u8]) => borrowed_bytes, owned_bytes;
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Picture<'a> {
+    #[serde(borrow)]
+    file: ExactStr<'a>,
+    #[serde(borrow, rename = "Title")]
+    title: Option<ExactStr<'a>>,
+    #[serde(borrow)]
+    data: ExactBytes<'a>,
+}
+
+test_deserialize! {
+    cow_borrowed;
+    |src| RequestDeserializer::from_read(Slice { src });
+    b"file: a.flac\nTitle: A\ndata: \x89PNG\nOK\n";
+    (_, &[u8], LexerState)
+
+    b"";
+    b"";
+    LexerState::Line;
+    Ok(Picture {
+        file: borrowed("a.flac"),
+        title: Some(borrowed("A")),
+        data: borrowed_bytes(b"\x89PNG"),
+    });
+}
+test_deserialize! {
+    cow_interrupt_owned;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"file: a.f", b"lac\nTitle: A\ndata: \x89P", b"NG\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"lac\nTitle: A\ndata: \x89P", b"NG\nOK\n"];
+    b"file: a.f";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b"NG\nOK\n"];
+    b"data: \x89P";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b""];
+    b"";
+    LexerState::Line;
+    Ok(Picture {
+        file: owned("a.flac"),
+        title: Some(borrowed("A")),
+        data: owned_bytes(b"\x89PNG"),
+    });
+}
+test_deserialize! {
+    cow_interrupt_records;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"file: a\nfile: ", b"b\nfile: c\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"b\nfile: c\nOK\n"];
+    b"file: ";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b""];
+    b"";
+    LexerState::Line;
+    Ok::<_, Error>(vec![borrowed("a"), owned("b"), borrowed("c")]);
+}
+test_deserialize! {
+    cow_interrupt_bytes_error;
+    |src| RequestDeserializer::from_read(InteruptSlice { src });
+    &mut [b"data: \x89P", b"NG\nOK\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
+
+    &[b"NG\nOK\n"];
+    b"data: \x89P";
+    LexerState::Line;
+    Err(Pending);
+
+    &[b"OK\n"];
+    b"";
+    LexerState::Line;
+    Err::<&[u8], Error>(Error::Custom(
+        "invalid type: byte array, expected a borrowed byte array; the value was split across \
+         chunks of the input and had to be copied, so it can't be borrowed \
+         (use `Vec<u8>` or `Cow<'de, [u8]>` instead)"
+            .to_string(),
+    ));
+}
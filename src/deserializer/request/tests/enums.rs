@@ -0,0 +1,36 @@
+use crate::{deserializer::request::RequestDeserializer, read::Slice, Error};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum State {
+    Play,
+    Stop,
+}
+
+#[test]
+fn unit_variant() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"state: play\nOK\nstate: stop\nOK\n",
+    });
+    assert_eq!(State::deserialize(&mut de), Ok(State::Play));
+    assert_eq!(State::deserialize(&mut de), Ok(State::Stop));
+}
+
+#[test]
+fn invalid() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"state: pause\nOK\nstate: play\nvolume: 5\nOK\n",
+    });
+    assert!(matches!(State::deserialize(&mut de), Err(Error::Custom(_))));
+    de.recover().unwrap();
+    assert!(matches!(State::deserialize(&mut de), Err(Error::Custom(_))));
+}
+
+#[test]
+fn ack() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"ACK [50@0] {status} error\n",
+    });
+    assert!(matches!(State::deserialize(&mut de), Err(Error::Ack(_))));
+}
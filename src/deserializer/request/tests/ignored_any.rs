@@ -1,7 +1,6 @@
 use crate::{
-    deserializer::request::{
-        tests::macros::test_deserialize, DeserializerState, RequestDeserializer,
-    },
+    deserializer::request::{tests::macros::test_deserialize, RequestDeserializer},
+    lexer::LexerState,
     read::{InteruptSlice, Slice},
     Error::Pending,
 };
@@ -10,123 +9,123 @@ use serde::de::IgnoredAny;
 test_deserialize! {
     ignore_any_ack;
     |src| RequestDeserializer::from_read(Slice { src });
-    b"ACK ...error message\n";
-    (_, &[u8], DeserializerState)
+    b"ACK [5@0] {} error message\n";
+    (_, &[u8], LexerState)
 
     b"";
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 test_deserialize! {
     ignore_any_ok_unit;
     |src| RequestDeserializer::from_read(Slice { src });
     b"OK\n";
-    (_, &[u8], DeserializerState)
+    (_, &[u8], LexerState)
 
     b"";
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 test_deserialize! {
     ignore_any_ok_entries;
     |src| RequestDeserializer::from_read(Slice { src });
     b"entry1: 1\nentry2: 2\nentry2: 3\nOK\n";
-    (_, &[u8], DeserializerState)
+    (_, &[u8], LexerState)
 
     b"";
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 
 test_deserialize! {
     ignore_any_interrupt_ack1;
     |src| RequestDeserializer::from_read(InteruptSlice { src });
-    &mut [b"ACK", b"", b" ...", b"error message\n"];
-    (&mut [&[u8]], &[&[u8]], DeserializerState)
+    &mut [b"ACK", b"", b" [5@0] {} ", b"error message\n"];
+    (&mut [&[u8]], &[&[u8]], LexerState)
 
-    &[b"", b" ...", b"error message\n"];
+    &[b"", b" [5@0] {} ", b"error message\n"];
     b"ACK";
-    DeserializerState::None;
+    LexerState::Line;
     Err(Pending);
 
-    &[b" ...", b"error message\n"];
+    &[b" [5@0] {} ", b"error message\n"];
     b"ACK";
-    DeserializerState::None;
+    LexerState::Line;
     Err(Pending);
 
     &[b"error message\n"];
-    b"";
-    DeserializerState::IgnoreAnyAck;
+    b"ACK [5@0] {} ";
+    LexerState::Line;
     Err(Pending);
 
     &[b""];
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 test_deserialize! {
     ignore_any_interrupt_ok_unit1;
     |src| RequestDeserializer::from_read(InteruptSlice { src });
     &mut [b"OK\n"];
-    (&mut [&[u8]], &[&[u8]], DeserializerState)
+    (&mut [&[u8]], &[&[u8]], LexerState)
 
     &[&[]];
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 test_deserialize! {
     ignore_any_interrupt_ok_entries1;
     |src| RequestDeserializer::from_read(InteruptSlice { src });
     &mut [b"entry1: 1\nentry2: 2\nentry2: 3\nOK\n"];
-    (&mut [&[u8]], &[&[u8]], DeserializerState)
+    (&mut [&[u8]], &[&[u8]], LexerState)
 
     &mut [&[]];
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
 test_deserialize! {
     ignore_any_ack_interrupt3;
     |src| RequestDeserializer::from_read(InteruptSlice { src });
     &mut [ b"", b"entry1", b": 1", b"\ne", b"ntry2: 2\nentry", b"2: 3\nO", b"K\n"];
-    (&mut [&[u8]], &[&[u8]], DeserializerState)
+    (&mut [&[u8]], &[&[u8]], LexerState)
 
     &[b"entry1", b": 1", b"\ne", b"ntry2: 2\nentry", b"2: 3\nO", b"K\n"];
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Err(Pending);
 
     &[b": 1", b"\ne", b"ntry2: 2\nentry", b"2: 3\nO", b"K\n"];
-    b"";
-    DeserializerState::None;
+    b"entry1";
+    LexerState::Line;
     Err(Pending);
 
     &[b"\ne", b"ntry2: 2\nentry", b"2: 3\nO", b"K\n"];
-    b"";
-    DeserializerState::None;
+    b"entry1: 1";
+    LexerState::Line;
     Err(Pending);
 
     &[b"ntry2: 2\nentry", b"2: 3\nO", b"K\n"];
-    b"";
-    DeserializerState::None;
+    b"e";
+    LexerState::Line;
     Err(Pending);
 
     &[b"2: 3\nO", b"K\n"];
-    b"";
-    DeserializerState::None;
+    b"entry";
+    LexerState::Line;
     Err(Pending);
 
     &[b"K\n"];
     b"O";
-    DeserializerState::None;
+    LexerState::Line;
     Err(Pending);
 
     &[b""];
     b"";
-    DeserializerState::None;
+    LexerState::Line;
     Ok(IgnoredAny);
 }
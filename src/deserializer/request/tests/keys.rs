@@ -0,0 +1,82 @@
+use crate::{deserializer::request::RequestDeserializer, read::Slice};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Song {
+    #[serde(rename = "file")]
+    file: String,
+    artist: Option<String>,
+    #[serde(rename = "MUSICBRAINZ_TRACKID")]
+    musicbrainz_trackid: Option<String>,
+    #[serde(rename = "Last-Modified")]
+    last_modified: Option<String>,
+}
+
+const SRC: &[u8] =
+    b"file: a\nartist: A\nMusicBrainz-TrackId: 1\nlast_modified: 2024\nFILE: b\nARTIST: B\nOK\n";
+
+#[test]
+fn normalized() {
+    let mut de = RequestDeserializer::from_read(Slice { src: SRC }).normalize_keys(true);
+    assert_eq!(
+        Vec::<Song>::deserialize(&mut de),
+        Ok(vec![
+            Song {
+                file: "a".to_string(),
+                artist: Some("A".to_string()),
+                musicbrainz_trackid: Some("1".to_string()),
+                last_modified: Some("2024".to_string()),
+            },
+            Song {
+                file: "b".to_string(),
+                artist: Some("B".to_string()),
+                musicbrainz_trackid: None,
+                last_modified: None,
+            },
+        ])
+    );
+}
+
+#[test]
+fn exact() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Status {
+        state: Option<String>,
+    }
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"State: play\nOK\n",
+    });
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { state: None }));
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"State: play\nOK\n",
+    })
+    .normalize_keys(true);
+    assert_eq!(
+        Status::deserialize(&mut de),
+        Ok(Status {
+            state: Some("play".to_string())
+        })
+    );
+}
+
+#[test]
+fn tags() {
+    use crate::{Field, Record, Tag, Value};
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"file: a\nArtist: A\nalbum: B\nFoo: C\nOK\n",
+    })
+    .tags(Some(vec![Tag::Artist]));
+    let field = |key: &str, value: &str| (key.to_string(), Field::Text(value.to_string()));
+    assert_eq!(
+        Value::deserialize(&mut de),
+        Ok(Value(vec![Record(vec![
+            field("file", "a"),
+            field("Artist", "A"),
+            field("Foo", "C"),
+        ])]))
+    );
+}
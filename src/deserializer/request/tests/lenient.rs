@@ -0,0 +1,96 @@
+use crate::{
+    deserializer::request::{RequestDeserializer, Warning},
+    read::{InteruptSlice, Slice},
+    Error,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Song {
+    #[serde(rename = "file")]
+    file: String,
+    track: Option<u32>,
+    #[serde(default)]
+    disc: u32,
+    #[serde(default)]
+    date: Vec<u16>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Status {
+    volume: u8,
+}
+
+fn warning(key: &str, message: &str) -> Warning {
+    Warning {
+        key: key.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn skip_invalid() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"file: a\nTrack: 3/12\nDisc: x\nDate: 1999\nDate: 1999-ish\nfile: b\nTrack: 4\nOK\n",
+    })
+    .lenient(true);
+    assert_eq!(
+        de.deserialize::<Vec<Song>>(),
+        Ok(vec![
+            Song {
+                file: "a".to_string(),
+                track: None,
+                disc: 0,
+                date: vec![1999],
+            },
+            Song {
+                file: "b".to_string(),
+                track: Some(4),
+                disc: 0,
+                date: Vec::new(),
+            },
+        ])
+    );
+    assert_eq!(
+        de.take_warnings(),
+        [
+            warning("Track", "invalid value: string \"3/12\", expected u32"),
+            warning("Disc", "invalid value: string \"x\", expected u32"),
+            warning("Date", "invalid value: string \"1999-ish\", expected u16"),
+        ]
+    );
+    assert!(de.warnings().is_empty());
+}
+
+#[test]
+fn strict() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"file: a\nTrack: 3/12\nOK\n",
+    });
+    assert_eq!(
+        de.deserialize::<Song>(),
+        Err(Error::Custom(
+            "invalid value: string \"3/12\", expected u32".to_string()
+        ))
+    );
+}
+
+#[test]
+fn consume_failed_response() {
+    let src: &mut [&[u8]] = &mut [b"volume: loud\nstate: ", b"play\nOK\nvolume: 5\nOK\n"];
+    let mut de = RequestDeserializer::from_read(InteruptSlice { src }).lenient(true);
+    assert_eq!(de.deserialize::<Status>(), Err(Error::Pending));
+    assert_eq!(
+        de.deserialize::<Status>(),
+        Err(Error::Custom("missing field `volume`".to_string()))
+    );
+    assert_eq!(
+        de.warnings(),
+        [warning(
+            "volume",
+            "invalid value: string \"loud\", expected u8"
+        )]
+    );
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { volume: 5 }));
+}
@@ -26,10 +26,10 @@ macro_rules! test_deserialize {
                 result = Deserialize::deserialize(&mut deserializer);
                 assert_eq!(result, $result);
                 let scratch: &[u8] = $scratch;
-                assert_eq!(SliceDebug(scratch), SliceDebug(deserializer.scratch.as_slice()));
+                assert_eq!(SliceDebug(scratch), SliceDebug(deserializer.lexer.scratch.as_slice()));
                 let input: $input_type = $input;
-                assert_eq!(SliceDebug(&*deserializer.read.src), SliceDebug(&*input));
-                assert_eq!(deserializer.state, $state);
+                assert_eq!(SliceDebug(&*deserializer.lexer.read.src), SliceDebug(&*input));
+                assert_eq!(deserializer.lexer.state, $state);
             )*
         }
     };
@@ -59,9 +59,9 @@ macro_rules! test_deserialize {
             let mut result;
             loop {
                 result = Deserialize::deserialize(&mut deserializer);
-                println!("{:?};", SliceDebug(&*deserializer.read.src));
-                println!("{:?};", SliceDebug(&*deserializer.scratch));
-                println!("{}::{:?};", core::any::type_name::<$state_type>(), deserializer.state);
+                println!("{:?};", SliceDebug(&*deserializer.lexer.read.src));
+                println!("{:?};", SliceDebug(&*deserializer.lexer.scratch));
+                println!("{}::{:?};", core::any::type_name::<$state_type>(), deserializer.lexer.state);
                 println!("{:?};", result);
                 println!();
                 if result.is_ok() { break; }
@@ -72,10 +72,10 @@ macro_rules! test_deserialize {
                 result = Deserialize::deserialize(&mut deserializer);
                 assert_eq!(result, $result);
                 let scratch: &[u8] = $scratch;
-                assert_eq!(SliceDebug(scratch), SliceDebug(deserializer.scratch.as_slice()));
+                assert_eq!(SliceDebug(scratch), SliceDebug(deserializer.lexer.scratch.as_slice()));
                 let input: $input_type = $input;
-                assert_eq!(SliceDebug(&*deserializer.read.src), SliceDebug(&*input));
-                assert_eq!(deserializer.state, $state);
+                assert_eq!(SliceDebug(&*deserializer.lexer.read.src), SliceDebug(&*input));
+                assert_eq!(deserializer.lexer.state, $state);
             )*
         }
     };
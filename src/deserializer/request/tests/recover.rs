@@ -0,0 +1,65 @@
+use crate::{
+    deserializer::request::RequestDeserializer,
+    read::{InteruptSlice, Slice},
+    Ack, Error,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Status {
+    volume: u8,
+}
+
+#[test]
+fn skip_rest() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"volume: loud\nstate: play\nbinary: 4\nOK\n\n\nOK\nvolume: 5\nOK\n",
+    });
+    assert!(Status::deserialize(&mut de).is_err());
+    assert_eq!(de.recover(), Ok(()));
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { volume: 5 }));
+    assert_eq!(de.recover(), Ok(()));
+}
+
+#[test]
+fn invalid_lines() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"volume: 1\nno pair\nACK [5@0] {} error\nvolume: 5\nOK\n",
+    });
+    assert!(Status::deserialize(&mut de).is_err());
+    assert_eq!(de.recover(), Ok(()));
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { volume: 5 }));
+}
+
+#[test]
+fn complete_response() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"ACK [5@0] {} error\nvolume: 5\nOK\n",
+    });
+    assert_eq!(
+        Status::deserialize(&mut de),
+        Err(Error::Ack(Ack {
+            code: 5,
+            index: 0,
+            command: String::new(),
+            message: "error".to_string(),
+        }))
+    );
+    assert_eq!(de.recover(), Ok(()));
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { volume: 5 }));
+}
+
+#[test]
+fn interrupted() {
+    let src: &mut [&[u8]] = &mut [
+        b"volume: x\nbinary: 5\nOK",
+        b"\nO",
+        b"K\n\nOK\nvolume: 5\nOK\n",
+    ];
+    let mut de = RequestDeserializer::from_read(InteruptSlice { src });
+    assert!(Status::deserialize(&mut de).is_err());
+    assert_eq!(de.recover(), Err(Error::Pending));
+    assert_eq!(de.recover(), Err(Error::Pending));
+    assert_eq!(de.recover(), Ok(()));
+    assert_eq!(Status::deserialize(&mut de), Ok(Status { volume: 5 }));
+}
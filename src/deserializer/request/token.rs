@@ -0,0 +1,48 @@
+use crate::{lexer::Event, Ack};
+use std::borrow::Cow;
+
+/// A single line of a response.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub(super) enum Token<'de> {
+    Pair {
+        key: Cow<'de, [u8]>,
+        value: Cow<'de, [u8]>,
+    },
+    /// Binary data, announced by a `binary: <length>` line.
+    Binary(Cow<'de, [u8]>),
+    ListOk,
+    Ok,
+    Ack(Ack),
+}
+
+/// The key and value of a `key: value` pair.
+pub(super) type Pair<'a, 'de> = (&'a Cow<'de, [u8]>, &'a Cow<'de, [u8]>);
+
+/// The key binary data is visited with.
+static BINARY: Cow<'static, [u8]> = Cow::Borrowed(b"binary");
+
+impl<'de> From<Event<'de, '_>> for Token<'de> {
+    fn from(event: Event<'de, '_>) -> Self {
+        match event {
+            Event::Pair { key, value } => Token::Pair {
+                key: key.into_cow(),
+                value: value.into_cow(),
+            },
+            Event::Binary(data) => Token::Binary(data.into_cow()),
+            Event::ListOk => Token::ListOk,
+            Event::Ok => Token::Ok,
+            Event::Ack(ack) => Token::Ack(ack),
+        }
+    }
+}
+
+impl<'de> Token<'de> {
+    /// Returns the key and value of a pair, binary data is treated as a pair with the key `binary`.
+    pub(super) fn pair(&self) -> Option<Pair<'_, 'de>> {
+        match self {
+            Token::Pair { key, value } => Some((key, value)),
+            Token::Binary(data) => Some((&BINARY, data)),
+            Token::ListOk | Token::Ok | Token::Ack(_) => None,
+        }
+    }
+}
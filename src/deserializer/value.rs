@@ -0,0 +1,361 @@
+use crate::{read::Reference, Error};
+use serde::{
+    de::{
+        value::{BorrowedStrDeserializer, SeqDeserializer, StrDeserializer},
+        DeserializeSeed, Error as _, Expected, SeqAccess, Unexpected, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Deserializes a single key or value of a `key: value` line or a single argument of a command.
+///
+/// Tuples are deserialized from values separated by `:`, like `0:5` or `44100:16:2`.
+pub(super) struct ValueDeserializer<'a, 'de> {
+    value: Reference<'de, 'a, [u8]>,
+    /// Whether an empty value is deserialized as `None`, used for the parts of tuples.
+    optional: bool,
+}
+
+impl<'a, 'de> ValueDeserializer<'a, 'de> {
+    pub(super) fn new(value: &'a Cow<'de, [u8]>) -> Self {
+        let value = match value {
+            Cow::Borrowed(borrowed) => Reference::Borrowed(*borrowed),
+            Cow::Owned(copied) => Reference::Copied(copied.as_slice()),
+        };
+        ValueDeserializer {
+            value,
+            optional: false,
+        }
+    }
+
+    fn str(&self) -> crate::Result<Reference<'de, 'a, str>> {
+        fn to_str(bytes: &[u8]) -> crate::Result<&str> {
+            std::str::from_utf8(bytes)
+                .map_err(|_| Error::invalid_value(Unexpected::Bytes(bytes), &"a utf-8 string"))
+        }
+        match self.value {
+            Reference::Borrowed(borrowed) => to_str(borrowed).map(Reference::Borrowed),
+            Reference::Copied(copied) => to_str(copied).map(Reference::Copied),
+        }
+    }
+
+    fn parse<T: FromStr>(&self, expected: &dyn serde::de::Expected) -> crate::Result<T> {
+        let str = self.str()?;
+        str.parse()
+            .map_err(|_| Error::invalid_value(Unexpected::Str(&str), expected))
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.parse(&visitor)?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+/// The error of visiting a value that was copied into the scratch buffer, which explains that it
+/// can't be borrowed if the visitor rejects it.
+#[derive(Debug)]
+struct Copied(Error);
+
+impl Display for Copied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for Copied {}
+
+impl serde::de::Error for Copied {
+    fn custom<T: Display>(msg: T) -> Self {
+        Copied(Error::custom(msg))
+    }
+
+    fn invalid_type(unexp: Unexpected, exp: &dyn Expected) -> Self {
+        // Values are only ever visited as non-borrowed if they had to be copied into the scratch
+        // buffer, which happens if they were split across multiple chunks of input.
+        let alternatives = match unexp {
+            Unexpected::Str(_) => "`String` or `Cow<'de, str>`",
+            Unexpected::Bytes(_) => "`Vec<u8>` or `Cow<'de, [u8]>`",
+            unexp => return Copied(Error::invalid_type(unexp, exp)),
+        };
+        Copied(Error::Custom(format!(
+            "invalid type: {unexp}, expected {exp}; the value was split across chunks of the \
+             input and had to be copied, so it can't be borrowed (use {alternatives} instead)"
+        )))
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // The visitor might not take strings at all, so rejecting a copied one isn't explained as
+        // a failure to borrow it.
+        match (self.str(), self.value) {
+            (Ok(Reference::Borrowed(borrowed)), _) => visitor.visit_borrowed_str(borrowed),
+            (Ok(Reference::Copied(copied)), _) => visitor.visit_str(copied),
+            (Err(_), Reference::Borrowed(borrowed)) => visitor.visit_borrowed_bytes(borrowed),
+            (Err(_), Reference::Copied(copied)) => visitor.visit_bytes(copied),
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_i8 => visit_i8
+        deserialize_i16 => visit_i16
+        deserialize_i32 => visit_i32
+        deserialize_i64 => visit_i64
+        deserialize_u8 => visit_u8
+        deserialize_u16 => visit_u16
+        deserialize_u32 => visit_u32
+        deserialize_u64 => visit_u64
+        deserialize_f32 => visit_f32
+        deserialize_f64 => visit_f64
+        deserialize_char => visit_char
+    }
+
+    forward_to_deserialize_any! {
+        seq map struct
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match &*self.value {
+            b"0" => visitor.visit_bool(false),
+            b"1" => visitor.visit_bool(true),
+            _ => Err(Error::invalid_value(
+                Unexpected::Str(&self.str()?),
+                &visitor,
+            )),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.str()? {
+            Reference::Borrowed(borrowed) => visitor.visit_borrowed_str(borrowed),
+            Reference::Copied(copied) => visitor.visit_str(copied).map_err(|Copied(error)| error),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Reference::Borrowed(borrowed) => visitor.visit_borrowed_bytes(borrowed),
+            Reference::Copied(copied) => visitor.visit_bytes(copied).map_err(|Copied(error)| error),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.optional && self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut parts = Parts {
+            rest: Some(self.value),
+            len,
+        };
+        let value = visitor.visit_seq(&mut parts)?;
+        match parts.rest {
+            None => Ok(value),
+            Some(_) => Err(Error::invalid_length(
+                self.value.split(|byte| *byte == b':').count(),
+                &format!("{len} values separated by `:`").as_str(),
+            )),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.str()? {
+            Reference::Borrowed(borrowed) => {
+                visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(borrowed))
+            }
+            Reference::Copied(copied) => visitor.visit_enum(StrDeserializer::<Error>::new(copied)),
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+/// The `:` separated parts of a value.
+struct Parts<'a, 'de> {
+    rest: Option<Reference<'de, 'a, [u8]>>,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Parts<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+
+        let Some(rest) = self.rest.take() else {
+            return seed.deserialize(Missing).map(Some);
+        };
+        let index = rest.iter().position(|byte| *byte == b':');
+        let (part, rest) = match (rest, index) {
+            (Reference::Borrowed(bytes), Some(index)) => (
+                Reference::Borrowed(&bytes[..index]),
+                Some(Reference::Borrowed(&bytes[index + 1..])),
+            ),
+            (Reference::Copied(bytes), Some(index)) => (
+                Reference::Copied(&bytes[..index]),
+                Some(Reference::Copied(&bytes[index + 1..])),
+            ),
+            (part, None) => (part, None),
+        };
+        self.rest = rest;
+
+        seed.deserialize(ValueDeserializer {
+            value: part,
+            optional: true,
+        })
+        .map(Some)
+    }
+}
+
+/// Deserializes a value that isn't there, which is only possible for `Option`s and sequences.
+pub(super) struct Missing;
+
+impl<'de> Deserializer<'de> for Missing {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Custom("missing value".to_string()))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::<_, Error>::new(std::iter::empty::<()>()))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
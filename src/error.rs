@@ -1,14 +1,23 @@
+use crate::read::SliceDebug;
 use std::{
     fmt::{self, Debug, Display, Formatter},
     io,
 };
 
+#[cfg(test)]
+mod tests;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Error {
+    /// Input or output that is well-formed, but doesn't match the type it was (de)serialized as.
     Custom(String),
+    /// Input that isn't well-formed, like a line that isn't `key: value` or an unclosed quote.
+    Syntax(String),
     Io(Io),
+    /// The server answered with an `ACK` instead of `OK`.
+    Ack(Ack),
     /// Some IO operation is pending. If this error is thrown it is safe to retry the operation.
     Pending,
     Eof,
@@ -18,15 +27,80 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         use Error::*;
         match self {
-            Custom(str) => f.write_str(str),
+            Custom(str) | Syntax(str) => f.write_str(str),
             Io(io) => Display::fmt(&io, f),
+            Ack(ack) => Display::fmt(&ack, f),
             Pending => f.write_str("io operations are pending"),
             Eof => f.write_str("unexpected end of file"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// Whether the operation can be retried once more input is available.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Error::Pending)
+    }
+
+    /// Whether the input ended before a response (or command) was complete.
+    pub fn is_eof(&self) -> bool {
+        matches!(self, Error::Eof)
+    }
+
+    pub fn is_io(&self) -> bool {
+        matches!(self, Error::Io(_))
+    }
+
+    /// Whether the server answered with an `ACK`, in which case the response was consumed
+    /// completely.
+    pub fn is_ack(&self) -> bool {
+        matches!(self, Error::Ack(_))
+    }
+
+    pub fn is_syntax(&self) -> bool {
+        matches!(self, Error::Syntax(_))
+    }
+
+    pub fn is_data(&self) -> bool {
+        matches!(self, Error::Custom(_))
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(io) => Some(&io.0),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(Io(error))
+    }
+}
+
+impl From<Error> for io::Error {
+    /// Keeps the wrapped error of [`Error::Io`], [`Error::Pending`] and [`Error::Eof`] become
+    /// [`io::ErrorKind::WouldBlock`] and [`io::ErrorKind::UnexpectedEof`].
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(io) => io.0,
+            Error::Pending => io::ErrorKind::WouldBlock.into(),
+            Error::Eof => io::ErrorKind::UnexpectedEof.into(),
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Error::Custom(msg.to_string())
+    }
+}
 impl serde::de::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -36,6 +110,96 @@ impl serde::de::Error for Error {
     }
 }
 
+/// An error response of the form `ACK [code@index] {command} message`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Ack {
+    pub code: u32,
+    /// Index of the failed command inside of a command list, `0` otherwise.
+    pub index: u32,
+    pub command: String,
+    pub message: String,
+}
+
+/// The error code of an [`Ack`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum AckKind {
+    NotList,
+    Arg,
+    /// The password was wrong.
+    Password,
+    /// The command isn't allowed without (a different) password.
+    Permission,
+    Unknown,
+    NoExist,
+    PlaylistMax,
+    System,
+    PlaylistLoad,
+    UpdateAlready,
+    PlayerSync,
+    Exist,
+    Other(u32),
+}
+
+impl Ack {
+    pub fn kind(&self) -> AckKind {
+        match self.code {
+            1 => AckKind::NotList,
+            2 => AckKind::Arg,
+            3 => AckKind::Password,
+            4 => AckKind::Permission,
+            5 => AckKind::Unknown,
+            50 => AckKind::NoExist,
+            51 => AckKind::PlaylistMax,
+            52 => AckKind::System,
+            53 => AckKind::PlaylistLoad,
+            54 => AckKind::UpdateAlready,
+            55 => AckKind::PlayerSync,
+            56 => AckKind::Exist,
+            code => AckKind::Other(code),
+        }
+    }
+
+    /// Parses everything following the `ACK ` of an ack line, excluding the trailing newline.
+    pub(crate) fn parse(line: &[u8]) -> Result<Ack> {
+        fn split(line: &[u8], start: u8, end: u8) -> Option<(&[u8], &[u8])> {
+            let line = line.strip_prefix(&[start])?;
+            let index = line.iter().position(|byte| *byte == end)?;
+            Some((&line[..index], &line[index + 1..]))
+        }
+        fn parse(line: &[u8]) -> Option<Ack> {
+            let (error, rest) = split(line, b'[', b']')?;
+            let (command, message) = split(rest.strip_prefix(b" ")?, b'{', b'}')?;
+
+            let index = error.iter().position(|byte| *byte == b'@')?;
+            let code = std::str::from_utf8(&error[..index]).ok()?.parse().ok()?;
+            let index = std::str::from_utf8(&error[index + 1..])
+                .ok()?
+                .parse()
+                .ok()?;
+
+            Some(Ack {
+                code,
+                index,
+                command: String::from_utf8_lossy(command).into_owned(),
+                message: String::from_utf8_lossy(message.strip_prefix(b" ").unwrap_or(message))
+                    .into_owned(),
+            })
+        }
+
+        parse(line).ok_or_else(|| Error::Syntax(format!("malformed ack {:?}", SliceDebug(line))))
+    }
+}
+
+impl Display for Ack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ACK [{}@{}] {{{}}} {}",
+            self.code, self.index, self.command, self.message
+        )
+    }
+}
+
 #[repr(transparent)]
 pub struct Io(pub io::Error);
 
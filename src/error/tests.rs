@@ -0,0 +1,43 @@
+use super::{Ack, Error, Io};
+use crate::{lexer::Lexer, read::Slice, RequestDeserializer};
+use serde::Deserialize;
+use std::io;
+
+#[test]
+fn classify() {
+    let mut lexer = Lexer::from_read(Slice { src: b"no pair\n" });
+    let error = lexer.next_event().unwrap_err();
+    assert!(error.is_syntax() && !error.is_data());
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"volume: loud\nOK\n",
+    });
+    let error = u8::deserialize(&mut de).unwrap_err();
+    assert!(error.is_data() && !error.is_syntax());
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"volume: 5\n",
+    });
+    assert!(u8::deserialize(&mut de).unwrap_err().is_eof());
+
+    assert!(Error::Pending.is_pending());
+    assert!(Error::Ack(Ack::default()).is_ack());
+}
+
+#[test]
+fn io() {
+    let error = Error::from(io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+    assert!(error.is_io());
+    assert_eq!(error, Error::Io(Io(io::ErrorKind::BrokenPipe.into())));
+    assert_ne!(error, Error::Io(Io(io::ErrorKind::WouldBlock.into())));
+    assert_eq!(io::Error::from(error).to_string(), "closed");
+
+    assert_eq!(
+        io::Error::from(Error::Pending).kind(),
+        io::ErrorKind::WouldBlock
+    );
+    assert_eq!(
+        io::Error::from(Error::Custom("invalid".to_string())).kind(),
+        io::ErrorKind::InvalidData
+    );
+}
@@ -0,0 +1,232 @@
+//! Filter expressions as used by `find`, `search` and similar commands, like
+//! `((Artist == 'A') AND (!(Album contains 'B')))`.
+//!
+//! [`Filter`] parses from and formats to the exact form of a filter argument, so it can be used as
+//! an argument with [`CommandSerializer`](crate::CommandSerializer) and
+//! [`CommandDeserializer`](crate::CommandDeserializer), which take care of quoting the argument
+//! itself.
+
+use self::parse::Parser;
+use crate::Error;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    ops::Not,
+    str::FromStr,
+};
+
+mod parse;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Filter {
+    /// `(TAG OP 'VALUE')`, where `TAG` may also be `any` to match any tag.
+    Tag {
+        tag: String,
+        operator: TagOperator,
+        value: String,
+    },
+    /// `(file == 'VALUE')`
+    File(String),
+    /// `(base 'VALUE')`
+    Base(String),
+    /// `(modified-since 'VALUE')`, with either a UNIX timestamp or an ISO 8601 time.
+    ModifiedSince(String),
+    /// `(added-since 'VALUE')`, with either a UNIX timestamp or an ISO 8601 time.
+    AddedSince(String),
+    /// `(AudioFormat OP 'VALUE')`
+    AudioFormat {
+        operator: AudioFormatOperator,
+        value: String,
+    },
+    /// `(prio >= VALUE)`
+    Prio(u8),
+    /// `(!EXPRESSION)`
+    Not(Box<Filter>),
+    /// `(EXPRESSION AND EXPRESSION ...)`
+    ///
+    /// Holds at least two filters when parsed or built with [`Filter::and`]. A single filter is
+    /// written as just that filter, an empty `AND` as `()`, which neither the server nor the
+    /// parser accept.
+    And(Vec<Filter>),
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TagOperator {
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `contains`
+    Contains,
+    /// `starts_with`
+    StartsWith,
+    /// `=~`
+    Regex,
+    /// `!~`
+    NotRegex,
+}
+
+impl TagOperator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TagOperator::Equal => "==",
+            TagOperator::NotEqual => "!=",
+            TagOperator::Contains => "contains",
+            TagOperator::StartsWith => "starts_with",
+            TagOperator::Regex => "=~",
+            TagOperator::NotRegex => "!~",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum AudioFormatOperator {
+    /// `==`, matching the audio format exactly.
+    Equal,
+    /// `=~`, matching a mask like `*:16:*`.
+    Mask,
+}
+
+impl AudioFormatOperator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AudioFormatOperator::Equal => "==",
+            AudioFormatOperator::Mask => "=~",
+        }
+    }
+}
+
+impl Filter {
+    pub fn tag(tag: impl Into<String>, operator: TagOperator, value: impl Into<String>) -> Self {
+        Filter::Tag {
+            tag: tag.into(),
+            operator,
+            value: value.into(),
+        }
+    }
+
+    /// Combines `self` and `other`, flattening nested `AND`s.
+    pub fn and(self, other: Filter) -> Self {
+        let mut filters = match self {
+            Filter::And(filters) => filters,
+            filter => vec![filter],
+        };
+        match other {
+            Filter::And(other) => filters.extend(other),
+            filter => filters.push(filter),
+        }
+        Filter::And(filters)
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).parse()
+    }
+}
+
+/// Writes `value` in double quotes, escaping quotes and backslashes.
+fn write_quoted(f: &mut Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\'' | '\\') {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Tag {
+                tag,
+                operator,
+                value,
+            } => {
+                write!(f, "({tag} {} ", operator.as_str())?;
+                write_quoted(f, value)?;
+            }
+            Filter::File(value) => {
+                f.write_str("(file == ")?;
+                write_quoted(f, value)?;
+            }
+            Filter::Base(value) => {
+                f.write_str("(base ")?;
+                write_quoted(f, value)?;
+            }
+            Filter::ModifiedSince(value) => {
+                f.write_str("(modified-since ")?;
+                write_quoted(f, value)?;
+            }
+            Filter::AddedSince(value) => {
+                f.write_str("(added-since ")?;
+                write_quoted(f, value)?;
+            }
+            Filter::AudioFormat { operator, value } => {
+                write!(f, "(AudioFormat {} ", operator.as_str())?;
+                write_quoted(f, value)?;
+            }
+            Filter::Prio(prio) => write!(f, "(prio >= {prio}")?,
+            Filter::Not(filter) => write!(f, "(!{filter}")?,
+            Filter::And(filters) if filters.len() == 1 => return write!(f, "{}", filters[0]),
+            Filter::And(filters) => {
+                f.write_char('(')?;
+                for (i, filter) in filters.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(" AND ")?;
+                    }
+                    write!(f, "{filter}")?;
+                }
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FilterVisitor;
+
+        impl Visitor<'_> for FilterVisitor {
+            type Value = Filter;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a filter expression")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                match std::str::from_utf8(v) {
+                    Ok(v) => self.visit_str(v),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(FilterVisitor)
+    }
+}
@@ -0,0 +1,173 @@
+use super::{AudioFormatOperator, Filter, TagOperator};
+use crate::Error;
+
+/// Recursive descent parser for filter expressions, following the grammar of the server.
+pub(super) struct Parser<'a> {
+    s: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(s: &'a str) -> Self {
+        Parser { s }
+    }
+
+    /// Parses a single expression, which has to make up the whole input.
+    pub(super) fn parse(mut self) -> crate::Result<Filter> {
+        self.skip_whitespace();
+        let filter = self.expression()?;
+        if !self.s.is_empty() {
+            return Err(self.error("the end"));
+        }
+        Ok(filter)
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        unexpected(expected, self.s)
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.s = self.s.trim_start();
+    }
+
+    /// Consumes `c` and any whitespace following it.
+    fn expect(&mut self, c: char) -> crate::Result<()> {
+        match self.s.strip_prefix(c) {
+            Some(rest) => {
+                self.s = rest;
+                self.skip_whitespace();
+                Ok(())
+            }
+            None => Err(self.error(&format!("`{c}`"))),
+        }
+    }
+
+    /// Consumes a run of characters matching `f` and any whitespace following it.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.s.find(|c| !f(c)).unwrap_or(self.s.len());
+        let (word, rest) = self.s.split_at(end);
+        self.s = rest;
+        self.skip_whitespace();
+        word
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+    }
+
+    fn operator(&mut self) -> &'a str {
+        self.take_while(|c| !c.is_whitespace() && !matches!(c, '\'' | '"' | '(' | ')'))
+    }
+
+    /// Parses a value quoted by single or double quotes, in which a backslash escapes the next
+    /// character.
+    fn quoted(&mut self) -> crate::Result<String> {
+        let mut chars = self.s.char_indices();
+        let quote = match chars.next() {
+            Some((_, quote @ ('\'' | '"'))) => quote,
+            _ => return Err(self.error("a quoted value")),
+        };
+
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some((i, c)) if c == quote => {
+                    self.s = &self.s[i + 1..];
+                    self.skip_whitespace();
+                    return Ok(value);
+                }
+                Some((_, '\\')) => value.extend(chars.next().map(|(_, c)| c)),
+                Some((_, c)) => value.push(c),
+                None => return Err(self.error("a closing quote")),
+            }
+        }
+    }
+
+    fn expression(&mut self) -> crate::Result<Filter> {
+        self.expect('(')?;
+
+        if self.s.starts_with('(') {
+            let mut filters = vec![self.expression()?];
+            while !self.s.starts_with(')') {
+                if self.word() != "AND" {
+                    return Err(self.error("`AND` or `)`"));
+                }
+                filters.push(self.expression()?);
+            }
+            self.expect(')')?;
+            return Ok(match filters.len() {
+                1 => filters.remove(0),
+                _ => Filter::And(filters),
+            });
+        }
+
+        if self.s.starts_with('!') {
+            self.expect('!')?;
+            let filter = self.expression()?;
+            self.expect(')')?;
+            return Ok(Filter::Not(Box::new(filter)));
+        }
+
+        let filter = match self.word() {
+            "" => return Err(self.error("a filter type")),
+            "base" => Filter::Base(self.quoted()?),
+            "modified-since" => Filter::ModifiedSince(self.quoted()?),
+            "added-since" => Filter::AddedSince(self.quoted()?),
+            "AudioFormat" => {
+                let operator = match self.operator() {
+                    "==" => AudioFormatOperator::Equal,
+                    "=~" => AudioFormatOperator::Mask,
+                    operator => return Err(unexpected("`==` or `=~`", operator)),
+                };
+                Filter::AudioFormat {
+                    operator,
+                    value: self.quoted()?,
+                }
+            }
+            "prio" => {
+                match self.operator() {
+                    ">=" => {}
+                    operator => return Err(unexpected("`>=`", operator)),
+                }
+                let prio = self.word();
+                Filter::Prio(
+                    prio.parse()
+                        .map_err(|_| Error::Syntax(format!("invalid priority {prio:?}")))?,
+                )
+            }
+            tag => {
+                let operator = match self.operator() {
+                    "==" => TagOperator::Equal,
+                    "!=" => TagOperator::NotEqual,
+                    "contains" => TagOperator::Contains,
+                    "starts_with" => TagOperator::StartsWith,
+                    "=~" => TagOperator::Regex,
+                    "!~" => TagOperator::NotRegex,
+                    operator => return Err(unexpected("a comparison operator", operator)),
+                };
+                let value = self.quoted()?;
+                match (tag, operator) {
+                    ("file", TagOperator::Equal) => Filter::File(value),
+                    _ => Filter::Tag {
+                        tag: tag.to_string(),
+                        operator,
+                        value,
+                    },
+                }
+            }
+        };
+
+        self.expect(')')?;
+        Ok(filter)
+    }
+}
+
+fn unexpected(expected: &str, found: &str) -> Error {
+    match found {
+        "" => Error::Syntax(format!(
+            "invalid filter: expected {expected}, found the end"
+        )),
+        found => Error::Syntax(format!(
+            "invalid filter: expected {expected}, found {found:?}"
+        )),
+    }
+}
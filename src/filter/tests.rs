@@ -0,0 +1,121 @@
+use super::{AudioFormatOperator, Filter, TagOperator};
+use crate::{read::Slice, CommandDeserializer, CommandSerializer, Error};
+use serde::{Deserialize, Serialize};
+
+#[track_caller]
+fn round_trip(filter: Filter, expected: &str) {
+    assert_eq!(filter.to_string(), expected);
+    assert_eq!(expected.parse::<Filter>(), Ok(filter));
+}
+
+#[test]
+fn tag() {
+    round_trip(
+        Filter::tag("Artist", TagOperator::Equal, "A"),
+        r#"(Artist == "A")"#,
+    );
+    round_trip(
+        Filter::tag("any", TagOperator::Contains, "it's \"B\" \\"),
+        r#"(any contains "it\'s \"B\" \\")"#,
+    );
+    round_trip(
+        Filter::tag("Title", TagOperator::NotRegex, "^x"),
+        r#"(Title !~ "^x")"#,
+    );
+}
+
+#[test]
+fn special() {
+    round_trip(Filter::File("a.flac".into()), r#"(file == "a.flac")"#);
+    round_trip(Filter::Base("music".into()), r#"(base "music")"#);
+    round_trip(
+        Filter::ModifiedSince("2024-01-01T00:00:00Z".into()),
+        r#"(modified-since "2024-01-01T00:00:00Z")"#,
+    );
+    round_trip(
+        Filter::AddedSince("1700000000".into()),
+        r#"(added-since "1700000000")"#,
+    );
+    round_trip(
+        Filter::AudioFormat {
+            operator: AudioFormatOperator::Mask,
+            value: "*:16:*".into(),
+        },
+        r#"(AudioFormat =~ "*:16:*")"#,
+    );
+    round_trip(Filter::Prio(42), "(prio >= 42)");
+}
+
+#[test]
+fn nested() {
+    let filter = Filter::tag("Artist", TagOperator::Equal, "A")
+        .and(!Filter::tag("Album", TagOperator::Contains, "B"))
+        .and(Filter::Prio(1));
+    round_trip(
+        filter.clone(),
+        r#"((Artist == "A") AND (!(Album contains "B")) AND (prio >= 1))"#,
+    );
+    assert_eq!(
+        "( (Artist == 'A')AND(! (Album contains 'B') )  AND (prio >= 1) )".parse(),
+        Ok(filter)
+    );
+    assert_eq!("((file == 'a'))".parse(), Ok(Filter::File("a".into())));
+}
+
+#[test]
+fn short_and() {
+    // A single filter is written as itself, so it parses back as that filter.
+    let filter = Filter::tag("Artist", TagOperator::Equal, "A");
+    let and = Filter::And(vec![filter.clone()]);
+    assert_eq!(and.to_string(), r#"(Artist == "A")"#);
+    assert_eq!(and.to_string().parse(), Ok(filter));
+    assert_eq!(Filter::And(Vec::new()).to_string(), "()");
+    assert!("()".parse::<Filter>().is_err());
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(
+        "(Artist = 'A')".parse::<Filter>(),
+        Err(Error::Syntax(
+            r#"invalid filter: expected a comparison operator, found "=""#.to_string()
+        ))
+    );
+    assert_eq!(
+        "(Artist == 'A".parse::<Filter>(),
+        Err(Error::Syntax(
+            r#"invalid filter: expected a closing quote, found "'A""#.to_string()
+        ))
+    );
+    assert_eq!(
+        "((Artist == 'A') OR (Album == 'B'))".parse::<Filter>(),
+        Err(Error::Syntax(
+            r#"invalid filter: expected `AND` or `)`, found "(Album == 'B'))""#.to_string()
+        ))
+    );
+    assert_eq!(
+        "(Artist == 'A') x".parse::<Filter>(),
+        Err(Error::Syntax(
+            r#"invalid filter: expected the end, found "x""#.to_string()
+        ))
+    );
+}
+
+#[test]
+fn command_argument() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum Command {
+        Find(Filter),
+    }
+
+    let command = Command::Find(Filter::tag("Artist", TagOperator::Equal, "x"));
+    let expected = b"find \"(Artist == \\\"x\\\")\"\n";
+
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    command.serialize(&mut ser).unwrap();
+    assert_eq!(ser.into_inner(), expected);
+
+    let mut de = CommandDeserializer::from_read(Slice { src: expected });
+    assert_eq!(Command::deserialize(&mut de), Ok(command));
+}
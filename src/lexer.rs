@@ -0,0 +1,192 @@
+//! Pull based access to the lines of responses, for when serde isn't the right tool.
+
+use crate::{
+    read::{Read, Reference, SliceDebug},
+    Ack, Error,
+};
+use std::ops::Range;
+
+#[cfg(test)]
+mod tests;
+
+/// A single line of a response, or the binary data announced by a `binary: <length>` line.
+///
+/// Keys, values and data that are contiguous in the input are borrowed from it, everything else
+/// references the scratch buffer of the [`Lexer`] and is only valid until the next event is read.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum Event<'de, 's> {
+    Pair {
+        key: Reference<'de, 's, [u8]>,
+        value: Reference<'de, 's, [u8]>,
+    },
+    Binary(Reference<'de, 's, [u8]>),
+    ListOk,
+    Ok,
+    Ack(Ack),
+}
+
+/// Splits the responses read from `R` into [`Event`]s.
+///
+/// If reading an event is interrupted by [`Error::Pending`], everything read so far is kept, so
+/// [`Lexer::next_event`] can simply be called again once more input is available.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Lexer<R> {
+    pub(crate) read: R,
+    pub(crate) scratch: Vec<u8>,
+    pub(crate) state: LexerState,
+    /// Whether the last event referenced `scratch`, which has to be cleared before reading on.
+    clear: bool,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub(crate) enum LexerState {
+    /// Reading a line.
+    #[default]
+    Line,
+    /// Reading binary data of which `usize` bytes are still missing.
+    Binary(usize),
+    /// Reading the newline following binary data.
+    BinaryEnd,
+}
+
+/// Where a line is stored, so it can be looked at without keeping `scratch` borrowed.
+enum Line<'de> {
+    Borrowed(&'de [u8]),
+    Copied(Range<usize>),
+}
+
+impl<'de, R: Read<'de>> Lexer<R> {
+    pub fn from_read(read: R) -> Self {
+        Lexer {
+            read,
+            scratch: Vec::new(),
+            state: LexerState::Line,
+            clear: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+
+    pub fn next_event(&mut self) -> crate::Result<Event<'de, '_>> {
+        self.release();
+
+        loop {
+            match self.state {
+                LexerState::Binary(len) => {
+                    return match self.read.read_len(&mut self.scratch, len) {
+                        Ok(data) => {
+                            self.state = LexerState::BinaryEnd;
+                            self.clear = true;
+                            Ok(Event::Binary(data))
+                        }
+                        Err((read, error)) => {
+                            self.state = LexerState::Binary(len - read);
+                            Err(error)
+                        }
+                    };
+                }
+                LexerState::BinaryEnd => match self.read.next()? {
+                    Some(b'\n') => self.state = LexerState::Line,
+                    Some(_) => {
+                        return Err(Error::Syntax(
+                            "expected newline after binary data".to_string(),
+                        ))
+                    }
+                    None => return Err(Error::Eof),
+                },
+                LexerState::Line => {
+                    let line = self.line()?;
+                    let len = match &line {
+                        Line::Borrowed(line) => binary_len(line),
+                        Line::Copied(range) => binary_len(&self.scratch[range.clone()]),
+                    };
+                    self.clear = true;
+                    match len {
+                        Ok(Some(len)) => {
+                            self.scratch.clear();
+                            self.clear = false;
+                            self.state = LexerState::Binary(len);
+                        }
+                        Ok(None) => {
+                            return Event::parse(match line {
+                                Line::Borrowed(line) => Reference::Borrowed(line),
+                                Line::Copied(range) => Reference::Copied(&self.scratch[range]),
+                            })
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Clears the scratch buffer, once the last event isn't referenced anymore.
+    pub(crate) fn release(&mut self) {
+        if std::mem::take(&mut self.clear) {
+            self.scratch.clear();
+        }
+    }
+
+    fn line(&mut self) -> crate::Result<Line<'de>> {
+        Ok(match self.read.read_until(&mut self.scratch, b'\n')? {
+            Reference::Borrowed(line) => Line::Borrowed(line),
+            Reference::Copied(line) => {
+                let (address, len) = (line.as_ptr() as usize, line.len());
+                let start = address - self.scratch.as_ptr() as usize;
+                Line::Copied(start..start + len)
+            }
+        })
+    }
+}
+
+impl<'de, 's> Event<'de, 's> {
+    /// Parses a line without its trailing newline.
+    fn parse(line: Reference<'de, 's, [u8]>) -> crate::Result<Self> {
+        match &*line {
+            b"OK" => return Ok(Event::Ok),
+            b"list_OK" => return Ok(Event::ListOk),
+            [b'A', b'C', b'K', b' ', ack @ ..] => return Ack::parse(ack).map(Event::Ack),
+            _ => {}
+        }
+
+        let pair = match line {
+            Reference::Borrowed(line) => split_pair(line)
+                .map(|(key, value)| (Reference::Borrowed(key), Reference::Borrowed(value))),
+            Reference::Copied(line) => split_pair(line)
+                .map(|(key, value)| (Reference::Copied(key), Reference::Copied(value))),
+        };
+        match pair {
+            Some((key, value)) => Ok(Event::Pair { key, value }),
+            None => Err(Error::Syntax(format!(
+                "expected `key: value`, found {:?}",
+                SliceDebug(&*line)
+            ))),
+        }
+    }
+}
+
+/// Returns the length of the binary data announced by a `binary: <length>` line.
+fn binary_len(line: &[u8]) -> crate::Result<Option<usize>> {
+    match split_pair(line) {
+        Some((b"binary", len)) => std::str::from_utf8(len)
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .map(Some)
+            .ok_or_else(|| {
+                Error::Syntax(format!(
+                    "invalid binary length {:?}",
+                    String::from_utf8_lossy(len)
+                ))
+            }),
+        _ => Ok(None),
+    }
+}
+
+/// Splits a `key: value` line into its key and value.
+fn split_pair(line: &[u8]) -> Option<(&[u8], &[u8])> {
+    let index = line.iter().position(|byte| *byte == b':')?;
+    let (key, value) = (&line[..index], &line[index + 1..]);
+    Some((key, value.strip_prefix(b" ").unwrap_or(value)))
+}
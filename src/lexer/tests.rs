@@ -0,0 +1,97 @@
+use super::{Event, Lexer, LexerState};
+use crate::{
+    read::{InteruptSlice, Reference, Slice},
+    Ack, Error,
+};
+
+#[test]
+fn events() {
+    let mut lexer = Lexer::from_read(Slice {
+        src: b"file: a.flac\nbinary: 3\n\x00\n\x01\nlist_OK\nACK [5@0] {} unknown\nOK\n",
+    });
+
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Pair {
+            key: Reference::Borrowed(b"file"),
+            value: Reference::Borrowed(b"a.flac"),
+        })
+    );
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Binary(Reference::Borrowed(b"\x00\n\x01")))
+    );
+    assert_eq!(lexer.next_event(), Ok(Event::ListOk));
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Ack(Ack {
+            code: 5,
+            index: 0,
+            command: String::new(),
+            message: "unknown".to_string(),
+        }))
+    );
+    assert_eq!(lexer.next_event(), Ok(Event::Ok));
+    assert_eq!(lexer.next_event(), Err(Error::Eof));
+}
+
+#[test]
+fn interrupted_pair() {
+    let src: &mut [&[u8]] = &mut [b"Title: ", b"A\nArtist: B\n", b"OK\n"];
+    let mut lexer = Lexer::from_read(InteruptSlice { src });
+
+    assert_eq!(lexer.next_event(), Err(Error::Pending));
+    assert_eq!(lexer.scratch, b"Title: ");
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Pair {
+            key: Reference::Copied(b"Title"),
+            value: Reference::Copied(b"A"),
+        })
+    );
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Pair {
+            key: Reference::Borrowed(b"Artist"),
+            value: Reference::Borrowed(b"B"),
+        })
+    );
+    assert!(lexer.scratch.is_empty());
+    assert_eq!(lexer.next_event(), Err(Error::Pending));
+    assert_eq!(lexer.next_event(), Ok(Event::Ok));
+}
+
+#[test]
+fn interrupted_binary() {
+    let src: &mut [&[u8]] = &mut [b"binary: 4\n\x00\x01", b"\x02\x03", b"\nOK\n"];
+    let mut lexer = Lexer::from_read(InteruptSlice { src });
+
+    assert_eq!(lexer.next_event(), Err(Error::Pending));
+    assert_eq!(lexer.state, LexerState::Binary(2));
+    assert_eq!(
+        lexer.next_event(),
+        Ok(Event::Binary(Reference::Copied(b"\x00\x01\x02\x03")))
+    );
+    assert_eq!(lexer.state, LexerState::BinaryEnd);
+    assert_eq!(lexer.next_event(), Err(Error::Pending));
+    assert_eq!(lexer.next_event(), Ok(Event::Ok));
+    assert_eq!(lexer.state, LexerState::Line);
+}
+
+#[test]
+fn invalid_lines() {
+    let mut lexer = Lexer::from_read(Slice {
+        src: b"no pair\nbinary: x\n",
+    });
+
+    assert_eq!(
+        lexer.next_event(),
+        Err(Error::Syntax(
+            "expected `key: value`, found b\"no pair\"".to_string()
+        ))
+    );
+    assert_eq!(
+        lexer.next_event(),
+        Err(Error::Syntax("invalid binary length \"x\"".to_string()))
+    );
+}
@@ -1,5 +1,25 @@
-pub use error::{Error, Result};
+pub use deserializer::{CommandDeserializer, RequestDeserializer, Warning};
+pub use error::{Ack, AckKind, Error, Result};
+pub use serializer::{CommandSerializer, ResponseSerializer};
+pub use tag::Tag;
+pub use value::{from_value, Field, Record, Value};
+pub use version::Version;
 
+pub mod client;
+#[cfg(feature = "codec")]
+pub mod codec;
 mod deserializer;
 mod error;
+pub mod filter;
+pub mod lexer;
+pub mod model;
+pub mod protocol;
+#[cfg(feature = "proxy")]
+pub mod proxy;
 pub mod read;
+mod serializer;
+mod tag;
+#[cfg(all(any(test, feature = "testing"), unix))]
+pub mod testing;
+mod value;
+mod version;
@@ -0,0 +1,192 @@
+//! Types for the responses of common commands, as returned by [`Client`](crate::client::Client).
+
+use crate::{filter::Filter, Tag};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// `state` of [`Status`].
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Play,
+    Stop,
+    Pause,
+}
+
+/// Response of `status`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, PartialOrd, Debug)]
+pub struct Status {
+    /// `None` if there is no mixer, older servers send `-1` instead.
+    pub volume: Option<i8>,
+    pub repeat: bool,
+    pub random: bool,
+    /// `0`, `1` or `oneshot`.
+    pub single: String,
+    /// `0`, `1` or `oneshot`.
+    pub consume: String,
+    pub playlist: u32,
+    pub playlistlength: u32,
+    pub state: State,
+    /// Position of the current song in the queue.
+    pub song: Option<u32>,
+    pub songid: Option<u32>,
+    pub nextsong: Option<u32>,
+    pub nextsongid: Option<u32>,
+    pub elapsed: Option<f64>,
+    pub duration: Option<f64>,
+    pub bitrate: Option<u32>,
+    pub audio: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A song, as returned by `currentsong`, `playlistinfo` or `find`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, PartialOrd, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Song {
+    #[serde(rename = "file")]
+    pub file: String,
+    #[serde(rename = "Last-Modified")]
+    pub last_modified: Option<String>,
+    #[serde(rename = "duration")]
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artist: Vec<String>,
+    pub album: Option<String>,
+    pub title: Option<String>,
+    pub track: Option<String>,
+    /// Position in the queue, only for songs in the queue.
+    pub pos: Option<u32>,
+    /// Id in the queue, only for songs in the queue.
+    pub id: Option<u32>,
+}
+
+/// A subsystem of the server, as reported as `changed` by `idle`.
+///
+/// Names of unknown subsystems are kept as [`Subsystem::Other`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Subsystem {
+    Database,
+    Update,
+    StoredPlaylist,
+    Playlist,
+    Player,
+    Mixer,
+    Output,
+    Options,
+    Partition,
+    Sticker,
+    Subscription,
+    Message,
+    Neighbor,
+    Mount,
+    Other(String),
+}
+
+impl Subsystem {
+    /// All known subsystems, without [`Subsystem::Other`].
+    pub const ALL: &'static [Subsystem] = &[
+        Subsystem::Database,
+        Subsystem::Update,
+        Subsystem::StoredPlaylist,
+        Subsystem::Playlist,
+        Subsystem::Player,
+        Subsystem::Mixer,
+        Subsystem::Output,
+        Subsystem::Options,
+        Subsystem::Partition,
+        Subsystem::Sticker,
+        Subsystem::Subscription,
+        Subsystem::Message,
+        Subsystem::Neighbor,
+        Subsystem::Mount,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Subsystem::Database => "database",
+            Subsystem::Update => "update",
+            Subsystem::StoredPlaylist => "stored_playlist",
+            Subsystem::Playlist => "playlist",
+            Subsystem::Player => "player",
+            Subsystem::Mixer => "mixer",
+            Subsystem::Output => "output",
+            Subsystem::Options => "options",
+            Subsystem::Partition => "partition",
+            Subsystem::Sticker => "sticker",
+            Subsystem::Subscription => "subscription",
+            Subsystem::Message => "message",
+            Subsystem::Neighbor => "neighbor",
+            Subsystem::Mount => "mount",
+            Subsystem::Other(name) => name,
+        }
+    }
+}
+
+impl FromStr for Subsystem {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let subsystem = Subsystem::ALL
+            .iter()
+            .find(|subsystem| subsystem.as_str() == s)
+            .cloned();
+        Ok(subsystem.unwrap_or_else(|| Subsystem::Other(s.to_string())))
+    }
+}
+
+impl Display for Subsystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Subsystem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Subsystem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Ok(subsystem) = String::deserialize(deserializer)?.parse();
+        Ok(subsystem)
+    }
+}
+
+/// Response of `idle`, empty if it was canceled by `noidle`.
+#[derive(Deserialize, Debug)]
+pub(crate) struct Changed {
+    #[serde(default)]
+    pub changed: Vec<Subsystem>,
+}
+
+/// Response of `tagtypes`.
+#[derive(Deserialize, Debug)]
+pub(crate) struct TagTypes {
+    #[serde(default)]
+    pub tagtype: Vec<Tag>,
+}
+
+/// The commands sent by the typed methods of the clients.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Request<'a> {
+    Password(&'a str),
+    Binarylimit(u64),
+    /// `tagtypes` listing the enabled tags.
+    #[serde(rename = "tagtypes")]
+    TagtypesList,
+    /// `tagtypes clear` or `tagtypes enable TAG...`.
+    Tagtypes(&'static str, &'a [Tag]),
+    Status,
+    Currentsong,
+    Play(Option<u32>),
+    Playlistinfo,
+    Find(&'a Filter),
+    /// `idle`, for changes of the given subsystems or of all of them if there are none.
+    Idle(&'a [Subsystem]),
+}
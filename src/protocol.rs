@@ -0,0 +1,263 @@
+//! A sans-IO implementation of the client side of the protocol.
+//!
+//! [`Protocol`] doesn't do any IO itself: commands are queued with [`Protocol::send`] and the
+//! bytes to transmit are taken with [`Protocol::take_outgoing`], bytes received from the server
+//! are handed to [`Protocol::receive`] and complete [`Response`]s are taken with
+//! [`Protocol::poll`], in the order the commands were sent.
+
+use crate::{
+    lexer::{Event, Lexer, LexerState},
+    read::{Slice, SliceDebug},
+    CommandSerializer, Error, RequestDeserializer, Version,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod tests;
+
+/// A command (or command list) that was sent and is waiting for its response.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Command {
+    /// A single command, by name.
+    Single(String),
+    /// A command list started by `command_list_begin` or, if `ok` is set, by
+    /// `command_list_ok_begin`, in which case the response of every command in it is terminated by
+    /// `list_OK`.
+    List { ok: bool, commands: Vec<String> },
+}
+
+impl Command {
+    /// Returns the name of the command, or `command_list_begin`/`command_list_ok_begin`.
+    pub fn name(&self) -> &str {
+        match self {
+            Command::Single(name) => name,
+            Command::List { ok: false, .. } => "command_list_begin",
+            Command::List { ok: true, .. } => "command_list_ok_begin",
+        }
+    }
+}
+
+/// The complete response to a [`Command`], including the `OK` or `ACK` terminating it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Response {
+    pub command: Command,
+    pub data: Vec<u8>,
+}
+
+impl Response {
+    /// Returns a deserializer over the response.
+    ///
+    /// The response of a command list sent with `command_list_ok_begin` can be deserialized by
+    /// deserializing once for every command in it.
+    ///
+    /// The deserializer doesn't know the protocol version, so fields annotated with one (see
+    /// [`Version`]) are optional, unless [`RequestDeserializer::version`] is set to
+    /// [`Protocol::version`].
+    pub fn deserializer(&self) -> RequestDeserializer<'_, Slice<'_>> {
+        RequestDeserializer::from_read(Slice { src: &self.data })
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>>(&'de self) -> crate::Result<T> {
+        T::deserialize(&mut self.deserializer())
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Protocol {
+    /// Protocol version announced by the server, once the greeting was received.
+    version: Option<Version>,
+    outgoing: Vec<u8>,
+    incoming: Vec<u8>,
+    in_flight: VecDeque<Command>,
+    /// Command list that was started, but not yet ended, see [`Command::List`].
+    list: Option<(bool, Vec<String>)>,
+    /// Whether an `idle` is in flight and wasn't canceled by `noidle` yet.
+    idle: bool,
+    /// Length of the complete lines at the start of `incoming` that were already scanned by
+    /// [`Protocol::poll`], so they aren't scanned again.
+    scanned: usize,
+}
+
+impl Protocol {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the protocol version the server announced in its greeting.
+    pub fn version(&self) -> Option<Version> {
+        self.version
+    }
+
+    /// Returns the commands that were sent, but whose response wasn't polled yet.
+    pub fn in_flight(&self) -> impl Iterator<Item = &Command> {
+        self.in_flight.iter()
+    }
+
+    /// Whether a command list was begun, but not ended yet.
+    pub fn in_list(&self) -> bool {
+        self.list.is_some()
+    }
+
+    /// Whether an `idle` is in flight that wasn't canceled, during which only `noidle` may be sent.
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Serializes `command` with [`CommandSerializer`] and queues it.
+    pub fn send<T: Serialize + ?Sized>(&mut self, command: &T) -> crate::Result<()> {
+        let mut ser = CommandSerializer::from_write(Vec::new());
+        command.serialize(&mut ser)?;
+        self.send_raw(&ser.into_inner())
+    }
+
+    /// Queues already serialized command lines, which have to be terminated by a newline.
+    pub fn send_raw(&mut self, lines: &[u8]) -> crate::Result<()> {
+        let Some(lines) = lines.strip_suffix(b"\n") else {
+            return Err(Error::Custom(format!(
+                "expected command lines terminated by a newline, found {:?}",
+                SliceDebug(lines)
+            )));
+        };
+
+        // Work on copies, so nothing is queued if any of the lines is rejected.
+        let mut in_flight = Vec::new();
+        let mut list = self.list.clone();
+        let mut idle = self.idle;
+        for line in lines.split(|byte| *byte == b'\n') {
+            let name = line
+                .split(|byte| matches!(byte, b' ' | b'\t'))
+                .next()
+                .unwrap_or_default();
+            let name = String::from_utf8_lossy(name).into_owned();
+
+            match (&mut list, name.as_str()) {
+                (_, "") => return Err(Error::Custom("empty command line".to_string())),
+                (_, "noidle") if idle => idle = false,
+                (_, _) if idle => {
+                    return Err(Error::Custom(format!(
+                        "can't send {name:?} while idle, send `noidle` first"
+                    )))
+                }
+                (None, "command_list_begin" | "command_list_ok_begin") => {
+                    list = Some((name == "command_list_ok_begin", Vec::new()))
+                }
+                (None, "command_list_end") => {
+                    return Err(Error::Custom(
+                        "`command_list_end` without a command list".to_string(),
+                    ))
+                }
+                (None, "noidle") => {}
+                (None, _) => {
+                    idle = name == "idle";
+                    in_flight.push(Command::Single(name));
+                }
+                (Some((ok, commands)), "command_list_end") => {
+                    in_flight.push(Command::List {
+                        ok: *ok,
+                        commands: std::mem::take(commands),
+                    });
+                    list = None;
+                }
+                (Some(_), "command_list_begin" | "command_list_ok_begin" | "idle" | "noidle") => {
+                    return Err(Error::Custom(format!(
+                        "can't send {name:?} inside of a command list"
+                    )))
+                }
+                (Some((_, commands)), _) => commands.push(name),
+            }
+        }
+
+        self.in_flight.extend(in_flight);
+        self.list = list;
+        self.idle = idle;
+        self.outgoing.extend_from_slice(lines);
+        self.outgoing.push(b'\n');
+        Ok(())
+    }
+
+    /// Takes the bytes that have to be transmitted to the server.
+    pub fn take_outgoing(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Hands bytes received from the server to the protocol.
+    pub fn receive(&mut self, bytes: &[u8]) {
+        self.incoming.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete response, or `None` if more bytes have to be received first.
+    pub fn poll(&mut self) -> crate::Result<Option<Response>> {
+        if self.version.is_none() {
+            let Some(end) = self.incoming.iter().position(|byte| *byte == b'\n') else {
+                return Ok(None);
+            };
+            let version = self.incoming[..end]
+                .strip_prefix(b"OK MPD ")
+                .ok_or_else(|| {
+                    Error::Syntax(format!(
+                        "expected greeting, found {:?}",
+                        SliceDebug(&self.incoming[..end])
+                    ))
+                })?;
+            self.version = Some(String::from_utf8_lossy(version).parse()?);
+            self.incoming.drain(..=end);
+        }
+
+        if self.incoming.is_empty() {
+            return Ok(None);
+        }
+        if self.in_flight.is_empty() {
+            return Err(Error::Custom(format!(
+                "received {:?} without a command in flight",
+                SliceDebug(self.incoming.as_slice())
+            )));
+        }
+
+        let Some(len) = response_len(&self.incoming, &mut self.scanned, false)? else {
+            return Ok(None);
+        };
+        let data = self.incoming.drain(..len).collect();
+        let command = self
+            .in_flight
+            .pop_front()
+            .expect("expected a command in flight");
+        if self.in_flight.is_empty() {
+            self.idle = false;
+        }
+        Ok(Some(Response { command, data }))
+    }
+}
+
+/// Returns the length of the first complete response in `bytes`, starting to scan after the
+/// `scanned` bytes, which are updated if the response isn't complete yet.
+///
+/// If `list_ok` is set, the response of a single command of a command list, terminated by
+/// `list_OK`, counts as complete too.
+pub(crate) fn response_len(
+    bytes: &[u8],
+    scanned: &mut usize,
+    list_ok: bool,
+) -> crate::Result<Option<usize>> {
+    let mut lexer = Lexer::from_read(Slice {
+        src: &bytes[*scanned..],
+    });
+    loop {
+        match lexer.next_event().map(|event| match event {
+            Event::Ok | Event::Ack(_) => true,
+            Event::ListOk => list_ok,
+            _ => false,
+        }) {
+            Ok(true) => {
+                *scanned = 0;
+                return Ok(Some(bytes.len() - lexer.read.src.len()));
+            }
+            Ok(false) if lexer.state == LexerState::Line => {
+                *scanned = bytes.len() - lexer.read.src.len();
+            }
+            Ok(false) => {}
+            Err(Error::Eof) => return Ok(None),
+            Err(error) => return Err(error),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use super::{Command, Protocol, Response};
+use crate::{Ack, Error, Version};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Request {
+    Status,
+    Play(Option<u32>),
+    Idle(Vec<String>),
+    Noidle,
+    Albumart(String, u64),
+    CommandListOkBegin(Vec<Request>),
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Status {
+    state: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Changed {
+    changed: Vec<String>,
+}
+
+fn single(name: &str) -> Command {
+    Command::Single(name.to_string())
+}
+
+/// Hands `bytes` to `protocol` one byte at a time and collects all responses.
+fn receive(protocol: &mut Protocol, bytes: &[u8]) -> Vec<Response> {
+    let mut responses = Vec::new();
+    for byte in bytes {
+        protocol.receive(&[*byte]);
+        while let Some(response) = protocol.poll().unwrap() {
+            responses.push(response);
+        }
+    }
+    responses
+}
+
+#[test]
+fn fifo() {
+    let mut protocol = Protocol::new();
+    protocol.send(&Request::Status).unwrap();
+    protocol.send(&Request::Play(Some(3))).unwrap();
+    protocol.send(&Request::Status).unwrap();
+    assert_eq!(protocol.take_outgoing(), b"status\nplay 3\nstatus\n");
+
+    let responses = receive(
+        &mut protocol,
+        b"OK MPD 0.23.5\nstate: stop\nOK\nACK [2@0] {play} Bad song index\nstate: play\nOK\n",
+    );
+    assert_eq!(protocol.version(), Some(Version::new(0, 23, 5)));
+    assert_eq!(
+        responses
+            .iter()
+            .map(|response| &response.command)
+            .collect::<Vec<_>>(),
+        [&single("status"), &single("play"), &single("status")]
+    );
+    assert_eq!(
+        responses[0].deserialize(),
+        Ok(Status {
+            state: "stop".to_string()
+        })
+    );
+    assert_eq!(
+        responses[1].deserialize::<()>(),
+        Err(Error::Ack(Ack {
+            code: 2,
+            index: 0,
+            command: "play".to_string(),
+            message: "Bad song index".to_string(),
+        }))
+    );
+    assert_eq!(
+        responses[2].deserialize(),
+        Ok(Status {
+            state: "play".to_string()
+        })
+    );
+    assert_eq!(protocol.in_flight().count(), 0);
+}
+
+#[test]
+fn command_list() {
+    let mut protocol = Protocol::new();
+    protocol
+        .send(&Request::CommandListOkBegin(vec![
+            Request::Status,
+            Request::Play(None),
+        ]))
+        .unwrap();
+    assert_eq!(
+        protocol.in_flight().collect::<Vec<_>>(),
+        [&Command::List {
+            ok: true,
+            commands: vec!["status".to_string(), "play".to_string()],
+        }]
+    );
+
+    let responses = receive(
+        &mut protocol,
+        b"OK MPD 0.23.5\nstate: stop\nlist_OK\nlist_OK\nOK\n",
+    );
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].command.name(), "command_list_ok_begin");
+
+    let mut de = responses[0].deserializer();
+    assert_eq!(
+        Status::deserialize(&mut de),
+        Ok(Status {
+            state: "stop".to_string()
+        })
+    );
+    assert_eq!(<()>::deserialize(&mut de), Ok(()));
+    assert_eq!(<()>::deserialize(&mut de), Ok(()));
+}
+
+#[test]
+fn command_list_split_across_sends() {
+    let mut protocol = Protocol::new();
+    protocol.send_raw(b"command_list_begin\nstatus\n").unwrap();
+    assert_eq!(protocol.in_flight().count(), 0);
+    protocol.send_raw(b"play 1\ncommand_list_end\n").unwrap();
+    assert_eq!(
+        protocol.in_flight().collect::<Vec<_>>(),
+        [&Command::List {
+            ok: false,
+            commands: vec!["status".to_string(), "play".to_string()],
+        }]
+    );
+    assert_eq!(
+        protocol.send_raw(b"command_list_end\n"),
+        Err(Error::Custom(
+            "`command_list_end` without a command list".to_string()
+        ))
+    );
+}
+
+#[test]
+fn idle() {
+    let mut protocol = Protocol::new();
+    protocol.receive(b"OK MPD 0.23.5\n");
+    assert_eq!(protocol.poll(), Ok(None));
+
+    protocol
+        .send(&Request::Idle(vec!["player".to_string()]))
+        .unwrap();
+    assert!(protocol.is_idle());
+    assert_eq!(
+        protocol.send(&Request::Status),
+        Err(Error::Custom(
+            "can't send \"status\" while idle, send `noidle` first".to_string()
+        ))
+    );
+
+    protocol.send(&Request::Noidle).unwrap();
+    assert!(!protocol.is_idle());
+    protocol.send(&Request::Status).unwrap();
+    assert_eq!(protocol.take_outgoing(), b"idle player\nnoidle\nstatus\n");
+
+    let responses = receive(&mut protocol, b"changed: player\nOK\nstate: play\nOK\n");
+    assert_eq!(responses[0].command, single("idle"));
+    assert_eq!(
+        responses[0].deserialize(),
+        Ok(Changed {
+            changed: vec!["player".to_string()]
+        })
+    );
+    assert_eq!(responses[1].command, single("status"));
+}
+
+#[test]
+fn binary() {
+    let mut protocol = Protocol::new();
+    protocol
+        .send(&Request::Albumart("a.flac".to_string(), 0))
+        .unwrap();
+
+    let responses = receive(
+        &mut protocol,
+        b"OK MPD 0.23.5\nsize: 2\nbinary: 5\nOK\n\x00\x01\nOK\n",
+    );
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].data, b"size: 2\nbinary: 5\nOK\n\x00\x01\nOK\n");
+}
+
+#[test]
+fn unexpected_data() {
+    let mut protocol = Protocol::new();
+    protocol.receive(b"OK MPD 0.23.5\nOK\n");
+    assert_eq!(
+        protocol.poll(),
+        Err(Error::Custom(
+            "received b\"OK\\n\" without a command in flight".to_string()
+        ))
+    );
+
+    let mut protocol = Protocol::new();
+    protocol.receive(b"hello\n");
+    assert_eq!(
+        protocol.poll(),
+        Err(Error::Syntax(
+            "expected greeting, found b\"hello\"".to_string()
+        ))
+    );
+
+    let mut protocol = Protocol::new();
+    protocol.receive(b"OK MPD 0.x\n");
+    assert_eq!(
+        protocol.poll(),
+        Err(Error::Syntax("invalid version \"0.x\"".to_string()))
+    );
+}
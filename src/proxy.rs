@@ -0,0 +1,280 @@
+//! A proxy sharing one upstream connection between many clients.
+//!
+//! Commands of all clients are pipelined over the upstream connection of an [`AsyncClient`],
+//! which keeps it in `idle` while no command is pending. The changes it reports are collected for
+//! every client until it asks for them with `idle`, just like a server does for each connection.
+
+use crate::{client::AsyncClient, model::Subsystem, read::Slice, Ack, CommandDeserializer, Error};
+use serde::{
+    de::{EnumAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Formatter},
+};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+#[cfg(all(test, unix))]
+mod tests;
+
+#[derive(Clone, Debug)]
+pub struct Proxy {
+    client: AsyncClient,
+}
+
+impl Proxy {
+    pub fn new(client: AsyncClient) -> Self {
+        Proxy { client }
+    }
+
+    /// Serves a single client connected through `stream`, until it closes the connection or
+    /// sends `close`.
+    ///
+    /// Fails if the upstream connection fails. Commands that change the state of the connection,
+    /// like `password`, `binarylimit` or `tagtypes`, affect all clients.
+    pub async fn serve<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S) -> crate::Result<()> {
+        let mut watcher = self.client.watch(Vec::new());
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(format!("OK MPD {}\n", self.client.version()).as_bytes())
+            .await?;
+
+        let mut changed = BTreeSet::new();
+        // Subsystems the client is waiting for with `idle`, all of them if it is empty.
+        let mut idle: Option<Vec<Subsystem>> = None;
+        let mut list: Option<List> = None;
+        // Kept across iterations, since reading a line is interrupted by changes.
+        let mut buffer = Vec::new();
+        loop {
+            if let Some(subsystems) = &idle {
+                let reported = take_changes(&mut changed, subsystems);
+                if !reported.is_empty() {
+                    writer.write_all(&reported).await?;
+                    writer.write_all(b"OK\n").await?;
+                    idle = None;
+                }
+            }
+
+            tokio::select! {
+                read = reader.read_until(b'\n', &mut buffer) => {
+                    if read? == 0 || !buffer.ends_with(b"\n") {
+                        return Ok(());
+                    }
+                }
+                subsystems = watcher.changed() => {
+                    match subsystems {
+                        Some(Ok(subsystems)) => changed.extend(subsystems),
+                        Some(Err(error)) => return Err(error),
+                        None => return Err(Error::Eof),
+                    }
+                    continue;
+                }
+            }
+
+            let line = std::mem::take(&mut buffer);
+            let parsed =
+                Line::deserialize(&mut CommandDeserializer::from_read(Slice { src: &line }));
+            if let Some(subsystems) = &idle {
+                if !matches!(parsed, Ok(Line::Noidle)) {
+                    return Err(Error::Custom(format!(
+                        "received {:?} while idle",
+                        String::from_utf8_lossy(&line).trim_end()
+                    )));
+                }
+                let reported = take_changes(&mut changed, subsystems);
+                writer.write_all(&reported).await?;
+                writer.write_all(b"OK\n").await?;
+                idle = None;
+                continue;
+            }
+
+            if let Some(begun) = &mut list {
+                match parsed {
+                    Ok(Line::ListEnd) => {
+                        let List {
+                            mut lines,
+                            len,
+                            failed,
+                        } = list.take().unwrap_or_default();
+                        if let Some(ack) = failed {
+                            write_ack(&mut writer, ack).await?;
+                        } else if len == 0 {
+                            writer.write_all(b"OK\n").await?;
+                        } else {
+                            lines.extend_from_slice(&line);
+                            self.forward(&mut writer, lines).await?;
+                        }
+                    }
+                    // Everything up to `command_list_end` is dropped once a line was rejected.
+                    _ if begun.failed.is_some() => {}
+                    Ok(Line::Other(_)) => {
+                        begun.lines.extend_from_slice(&line);
+                        begun.len += 1;
+                    }
+                    Ok(parsed) => {
+                        let message = "not allowed in a command list";
+                        begun.failed = Some(ack(begun.len, parsed.name(), message));
+                    }
+                    Err(error) => begun.failed = Some(malformed(begun.len, &line, &error)),
+                }
+                continue;
+            }
+
+            let parsed = match parsed {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    write_ack(&mut writer, malformed(0, &line, &error)).await?;
+                    continue;
+                }
+            };
+
+            match parsed {
+                Line::Idle(subsystems) => idle = Some(subsystems),
+                Line::Noidle => {}
+                Line::Close => return Ok(()),
+                Line::ListBegin(_) => {
+                    list = Some(List {
+                        lines: line,
+                        ..List::default()
+                    })
+                }
+                Line::ListEnd => {
+                    let message = "not in a command list";
+                    write_ack(&mut writer, ack(0, "command_list_end", message)).await?
+                }
+                Line::Other(_) => self.forward(&mut writer, line).await?,
+            }
+        }
+    }
+
+    /// Sends `lines` upstream and writes the response to `writer`.
+    async fn forward<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        lines: Vec<u8>,
+    ) -> crate::Result<()> {
+        match self.client.send_raw(lines).await {
+            Ok(response) => writer.write_all(&response.data).await?,
+            Err(Error::Ack(ack)) => write_ack(writer, ack).await?,
+            // The upstream connection failed, so the client is disconnected.
+            Err(error) => return Err(error),
+        }
+        Ok(())
+    }
+}
+
+/// A command list a client has begun, but not ended yet.
+#[derive(Default)]
+struct List {
+    /// The lines of the list, starting with `command_list_begin` or `command_list_ok_begin`.
+    lines: Vec<u8>,
+    /// The number of commands in the list.
+    len: u32,
+    /// The `ACK` for the first line that was rejected, which is sent instead of forwarding the
+    /// list once it ends.
+    failed: Option<Ack>,
+}
+
+/// An `ACK` of kind [`AckKind::Arg`](crate::AckKind::Arg) for a line the proxy rejects itself.
+fn ack(index: u32, command: &str, message: &str) -> Ack {
+    Ack {
+        code: 2,
+        index,
+        command: command.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// An `ACK` for a line that couldn't be parsed, naming the command if it could be read.
+fn malformed(index: u32, line: &[u8], error: &Error) -> Ack {
+    let line = String::from_utf8_lossy(line);
+    let name = line.split_whitespace().next().unwrap_or_default();
+    let name = if name.contains('"') { "" } else { name };
+    match error {
+        // The line couldn't be split into arguments. The message quotes its raw bytes, which
+        // don't belong on the wire.
+        Error::Syntax(_) => ack(index, name, "malformed command line"),
+        error => ack(index, name, &error.to_string()),
+    }
+}
+
+async fn write_ack<W: AsyncWrite + Unpin>(writer: &mut W, ack: Ack) -> crate::Result<()> {
+    writer.write_all(format!("{ack}\n").as_bytes()).await?;
+    Ok(())
+}
+
+/// A line sent by a client, as far as the proxy handles it itself.
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Line {
+    Idle(Vec<Subsystem>),
+    Noidle,
+    Close,
+    /// `command_list_begin` or `command_list_ok_begin`.
+    ListBegin(String),
+    ListEnd,
+    /// Any other command, which is forwarded as it is.
+    Other(String),
+}
+
+impl Line {
+    fn name(&self) -> &str {
+        match self {
+            Line::Idle(_) => "idle",
+            Line::Noidle => "noidle",
+            Line::Close => "close",
+            Line::ListBegin(name) | Line::Other(name) => name,
+            Line::ListEnd => "command_list_end",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Line {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LineVisitor;
+
+        impl<'de> Visitor<'de> for LineVisitor {
+            type Value = Line;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a command")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (name, variant) = data.variant::<String>()?;
+                Ok(match name.as_str() {
+                    "idle" => Line::Idle(variant.newtype_variant()?),
+                    "noidle" => {
+                        variant.unit_variant()?;
+                        Line::Noidle
+                    }
+                    "close" => {
+                        variant.unit_variant()?;
+                        Line::Close
+                    }
+                    // The commands of the list are read line by line, not through the variant.
+                    "command_list_begin" | "command_list_ok_begin" => Line::ListBegin(name),
+                    "command_list_end" => Line::ListEnd,
+                    _ => Line::Other(name),
+                })
+            }
+        }
+
+        deserializer.deserialize_enum("Line", &[], LineVisitor)
+    }
+}
+
+/// Removes the subsystems matching `subsystems` from `changed` and returns them as `changed`
+/// lines.
+fn take_changes(changed: &mut BTreeSet<Subsystem>, subsystems: &[Subsystem]) -> Vec<u8> {
+    let mut reported = Vec::new();
+    changed.retain(|subsystem| {
+        let matches = subsystems.is_empty() || subsystems.contains(subsystem);
+        if matches {
+            reported.extend_from_slice(format!("changed: {subsystem}\n").as_bytes());
+        }
+        !matches
+    });
+    reported
+}
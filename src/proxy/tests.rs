@@ -0,0 +1,176 @@
+use super::Proxy;
+use crate::{
+    client::{AsyncClient, Stream},
+    testing::{MockServer, Script},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, WriteHalf},
+    task::JoinHandle,
+};
+
+type Downstream = (
+    Lines<BufReader<tokio::io::ReadHalf<DuplexStream>>>,
+    WriteHalf<DuplexStream>,
+    JoinHandle<crate::Result<()>>,
+);
+
+/// Connects a proxy to a mock server running `script`.
+async fn proxy(script: Script) -> (Proxy, MockServer) {
+    let (upstream, server) = MockServer::pipe(script).unwrap();
+    let Stream::Unix(upstream) = upstream else {
+        unreachable!()
+    };
+    upstream.set_nonblocking(true).unwrap();
+    let upstream = tokio::net::UnixStream::from_std(upstream).unwrap();
+    (
+        Proxy::new(AsyncClient::new(upstream).await.unwrap()),
+        server,
+    )
+}
+
+/// Connects a client to `proxy` and reads the greeting.
+async fn connect(proxy: &Proxy) -> Downstream {
+    let (client, server) = tokio::io::duplex(4096);
+    let proxy = proxy.clone();
+    let handle = tokio::spawn(async move { proxy.serve(server).await });
+    let (reader, writer) = tokio::io::split(client);
+    let mut lines = BufReader::new(reader).lines();
+    assert_eq!(lines.next_line().await.unwrap().unwrap(), "OK MPD 0.23.5");
+    (lines, writer, handle)
+}
+
+/// Sends `command` and asserts the response lines, up to and including the `OK`.
+async fn exchange(downstream: &mut Downstream, command: &str, response: &[&str]) {
+    let (lines, writer, _) = downstream;
+    writer.write_all(command.as_bytes()).await.unwrap();
+    for line in response {
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), *line);
+    }
+}
+
+#[tokio::test]
+async fn fan_out() {
+    let script = Script::new()
+        .expect("idle", "")
+        .expect("noidle", "OK\n")
+        .expect("command_list_ok_begin", "")
+        .expect("status", "")
+        .expect("currentsong", "")
+        .expect(
+            "command_list_end",
+            "volume: 50\nlist_OK\nfile: a.flac\nlist_OK\nOK\n",
+        )
+        .expect("idle", "changed: player\nchanged: mixer\nOK\n")
+        .expect("idle", "");
+    let (proxy, server) = proxy(script).await;
+
+    let mut a = connect(&proxy).await;
+    let mut b = connect(&proxy).await;
+    exchange(
+        &mut a,
+        "command_list_ok_begin\nstatus\ncurrentsong\ncommand_list_end\n",
+        &["volume: 50", "list_OK", "file: a.flac", "list_OK", "OK"],
+    )
+    .await;
+
+    // Every client gets the changes reported upstream, filtered by its own `idle`.
+    exchange(&mut b, "idle player\n", &["changed: player", "OK"]).await;
+    exchange(&mut b, "idle player\nnoidle\n", &["OK"]).await;
+    exchange(
+        &mut a,
+        "idle\n",
+        &["changed: player", "changed: mixer", "OK"],
+    )
+    .await;
+    exchange(&mut b, "idle\n", &["changed: mixer", "OK"]).await;
+    // Commands the proxy handles itself aren't forwarded.
+    exchange(&mut a, "noidle\nclose\n", &[]).await;
+
+    let (_, _, a) = a;
+    a.await.unwrap().unwrap();
+    let (lines, writer, b) = b;
+    drop((lines, writer));
+    b.await.unwrap().unwrap();
+    drop(proxy);
+    tokio::task::spawn_blocking(move || server.finish())
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn rejected_lines() {
+    let script = Script::new()
+        .expect("idle", "")
+        .expect("noidle", "OK\n")
+        .expect("find \"(Artist == \\\"A B\\\")\"", "file: a.flac\nOK\n")
+        .expect("idle", "");
+    let (proxy, server) = proxy(script).await;
+
+    let mut a = connect(&proxy).await;
+    exchange(&mut a, "idle \"player\" mixer\nnoidle\n", &["OK"]).await;
+    // Nothing of a list is forwarded once a line was rejected, and it is answered only once.
+    exchange(
+        &mut a,
+        "command_list_begin\nstatus\nidle\nplay \"5\nstatus\ncommand_list_end\n",
+        &["ACK [2@1] {idle} not allowed in a command list"],
+    )
+    .await;
+    exchange(
+        &mut a,
+        "command_list_begin\nstatus\nplay \"5\nstatus\ncommand_list_end\n",
+        &["ACK [2@1] {play} malformed command line"],
+    )
+    .await;
+    exchange(&mut a, "command_list_begin\ncommand_list_end\n", &["OK"]).await;
+    exchange(
+        &mut a,
+        "play \"5\n",
+        &["ACK [2@0] {play} malformed command line"],
+    )
+    .await;
+    exchange(
+        &mut a,
+        "close 1\n",
+        &["ACK [2@0] {close} invalid length 1, expected no arguments"],
+    )
+    .await;
+    exchange(
+        &mut a,
+        "find \"(Artist == \\\"A B\\\")\"\n",
+        &["file: a.flac", "OK"],
+    )
+    .await;
+
+    let (lines, writer, a) = a;
+    drop((lines, writer));
+    a.await.unwrap().unwrap();
+    drop(proxy);
+    tokio::task::spawn_blocking(move || server.finish())
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn upstream_failure() {
+    let script = Script::new()
+        .expect("idle", "")
+        .expect("noidle", "OK\n")
+        .expect("status", "volume: 5\nOK\n")
+        .expect("idle", "ACK [5@0] {idle} unknown command\n")
+        .expect("idle", "");
+    let (proxy, server) = proxy(script).await;
+
+    let mut a = connect(&proxy).await;
+    exchange(&mut a, "status\n", &["volume: 5", "OK"]).await;
+    // The client is disconnected, instead of waiting for changes that never come.
+    let (mut lines, _writer, a) = a;
+    assert!(a.await.unwrap().unwrap_err().is_ack());
+    assert_eq!(lines.next_line().await.unwrap(), None);
+    drop(proxy);
+    tokio::task::spawn_blocking(move || server.finish())
+        .await
+        .unwrap()
+        .unwrap();
+}
@@ -1,9 +1,16 @@
 use crate::{Error, Result};
+use std::{borrow::Cow, ops::Deref};
 
-pub use self::{interupt_slice::InteruptSlice, slice::Slice, util::SliceDebug};
+pub use self::{
+    interupt_slice::InteruptSlice,
+    slice::Slice,
+    tee::{Tee, Transcript},
+    util::SliceDebug,
+};
 
 mod interupt_slice;
 mod slice;
+mod tee;
 mod util;
 
 pub trait Read<'de> {
@@ -34,6 +41,7 @@ pub trait Read<'de> {
     fn starts_with(&mut self, scratch: &mut Vec<u8>, starts_with: &[u8]) -> Result<bool>;
 }
 
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Reference<'de, 's, T>
 where
     T: ?Sized + 'static,
@@ -41,3 +49,40 @@ where
     Borrowed(&'de T),
     Copied(&'s T),
 }
+
+impl<'de, T> Reference<'de, '_, T>
+where
+    T: ?Sized + ToOwned + 'static,
+{
+    /// Keeps borrowed data borrowed and copies everything else.
+    pub fn into_cow(self) -> Cow<'de, T> {
+        match self {
+            Reference::Borrowed(borrowed) => Cow::Borrowed(borrowed),
+            Reference::Copied(copied) => Cow::Owned(copied.to_owned()),
+        }
+    }
+}
+
+impl<T> Clone for Reference<'_, '_, T>
+where
+    T: ?Sized + 'static,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Reference<'_, '_, T> where T: ?Sized + 'static {}
+
+impl<T> Deref for Reference<'_, '_, T>
+where
+    T: ?Sized + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match *self {
+            Reference::Borrowed(borrowed) => borrowed,
+            Reference::Copied(copied) => copied,
+        }
+    }
+}
@@ -66,6 +66,8 @@ impl<'a, 'de> Read<'de> for InteruptSlice<'a, 'de> {
                 } else {
                     unreachable!("We literally just checked this three lines above this");
                 }
+
+                self.src = slice;
             }
             [] => {}
         }
@@ -44,7 +44,9 @@ impl<'de> Read<'de> for Slice<'de> {
             .enumerate()
             .find(|(_, byte)| **byte == until)
             .ok_or(Error::Eof)?;
-        Ok(Reference::Borrowed(&self.src[..index]))
+        let (bytes, rest) = self.src.split_at(index);
+        self.src = &rest[1..];
+        Ok(Reference::Borrowed(bytes))
     }
     fn read_len<'s>(
         &mut self,
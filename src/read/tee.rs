@@ -0,0 +1,267 @@
+use crate::{
+    read::{Read, Reference},
+    Error,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Wraps a [`Read`] and records every byte consumed from it into a [`Transcript`].
+///
+/// Every [`Error::Pending`] returned by the wrapped reader starts a new chunk, so replaying the
+/// chunks with [`InteruptSlice`](crate::read::InteruptSlice) interrupts at the same points.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Tee<R> {
+    read: R,
+    transcript: Transcript,
+    /// The last byte returned by `peek`, which is consumed by `discard`.
+    peeked: Option<u8>,
+    /// Holds the caller's scratch buffer while `read` appends to it, so the appended bytes can be
+    /// recorded even if `read` fails. Empty otherwise.
+    scratch: Vec<u8>,
+}
+
+impl<R> Tee<R> {
+    pub fn new(read: R) -> Self {
+        Tee {
+            read,
+            transcript: Transcript {
+                chunks: vec![Vec::new()],
+            },
+            peeked: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    pub fn into_inner(self) -> (R, Transcript) {
+        (self.read, self.transcript)
+    }
+
+    fn record(&mut self, bytes: &[u8]) {
+        self.transcript
+            .chunks
+            .last_mut()
+            .expect("expected a chunk to record into")
+            .extend_from_slice(bytes);
+    }
+
+    /// Starts a new chunk if `result` is [`Error::Pending`].
+    fn interrupt<T>(&mut self, result: crate::Result<T>) -> crate::Result<T> {
+        if let Err(Error::Pending) = result {
+            self.transcript.chunks.push(Vec::new());
+        }
+        result
+    }
+}
+
+impl<'de, R: Read<'de>> Read<'de> for Tee<R> {
+    fn peek(&mut self) -> crate::Result<Option<u8>> {
+        let result = self.read.peek();
+        self.peeked = *result.as_ref().unwrap_or(&None);
+        self.interrupt(result)
+    }
+
+    fn next(&mut self) -> crate::Result<Option<u8>> {
+        self.peeked = None;
+        let result = self.read.next();
+        if let Ok(Some(byte)) = result {
+            self.record(&[byte]);
+        }
+        self.interrupt(result)
+    }
+
+    fn discard(&mut self) {
+        let byte = match self.peeked.take() {
+            Some(byte) => Some(byte),
+            // Nothing tells which byte is discarded without peeking at it first.
+            None => match self.read.peek() {
+                Ok(byte) => byte,
+                // `peek` already skipped over the end of the chunk `discard` would have skipped.
+                Err(error) => {
+                    let _ = self.interrupt::<()>(Err(error));
+                    return;
+                }
+            },
+        };
+        if let Some(byte) = byte {
+            self.record(&[byte]);
+        }
+        self.read.discard();
+    }
+
+    fn read_until<'s>(
+        &mut self,
+        scratch: &'s mut Vec<u8>,
+        until: u8,
+    ) -> crate::Result<Reference<'de, 's, [u8]>> {
+        self.peeked = None;
+        std::mem::swap(&mut self.scratch, scratch);
+        let before = self.scratch.len();
+        let consumed = self
+            .read
+            .read_until(&mut self.scratch, until)
+            .map(Consumed::from);
+        let result = self.consumed(consumed, before, scratch);
+        if result.is_ok() {
+            self.record(&[until]);
+        }
+        self.interrupt(result)
+    }
+
+    fn read_len<'s>(
+        &mut self,
+        scratch: &'s mut Vec<u8>,
+        len: usize,
+    ) -> std::result::Result<Reference<'de, 's, [u8]>, (usize, Error)> {
+        self.peeked = None;
+        std::mem::swap(&mut self.scratch, scratch);
+        let before = self.scratch.len();
+        let (consumed, read) = match self.read.read_len(&mut self.scratch, len) {
+            Ok(reference) => (Ok(Consumed::from(reference)), 0),
+            Err((read, error)) => (Err(error), read),
+        };
+        let result = self.consumed(consumed, before, scratch);
+        self.interrupt(result).map_err(|error| (read, error))
+    }
+
+    /// Reads the skipped bytes with [`Read::read_until`], so they can be recorded.
+    fn skip_until(&mut self, until: u8) -> crate::Result<()> {
+        let mut scratch = Vec::new();
+        self.read_until(&mut scratch, until).map(|_| ())
+    }
+
+    fn starts_with(&mut self, scratch: &mut Vec<u8>, starts_with: &[u8]) -> crate::Result<bool> {
+        self.peeked = None;
+        let before = scratch.len();
+        let result = self.read.starts_with(scratch, starts_with);
+        match result {
+            Ok(true) => self.record(starts_with.get(before..).unwrap_or_default()),
+            Ok(false) => {}
+            Err(_) => self.record(&scratch[before.min(scratch.len())..]),
+        }
+        self.interrupt(result)
+    }
+}
+
+/// Where the bytes returned by the wrapped reader are, so [`Tee::scratch`] isn't kept borrowed.
+enum Consumed<'de> {
+    Borrowed(&'de [u8]),
+    /// Address and length of bytes in [`Tee::scratch`].
+    Copied(usize, usize),
+}
+
+impl<'de> From<Reference<'de, '_, [u8]>> for Consumed<'de> {
+    fn from(reference: Reference<'de, '_, [u8]>) -> Self {
+        match reference {
+            Reference::Borrowed(bytes) => Consumed::Borrowed(bytes),
+            Reference::Copied(bytes) => Consumed::Copied(bytes.as_ptr() as usize, bytes.len()),
+        }
+    }
+}
+
+impl<'de, R> Tee<R> {
+    /// Records the bytes consumed by a call to the wrapped reader, including the ones it appended
+    /// to [`Tee::scratch`] after its first `before` bytes, and swaps it back into the caller's
+    /// `scratch`, which copied references point into then.
+    fn consumed<'s>(
+        &mut self,
+        consumed: crate::Result<Consumed<'de>>,
+        before: usize,
+        scratch: &'s mut Vec<u8>,
+    ) -> crate::Result<Reference<'de, 's, [u8]>> {
+        let appended = self.scratch.get(before..).unwrap_or_default();
+        self.transcript
+            .chunks
+            .last_mut()
+            .expect("expected a chunk to record into")
+            .extend_from_slice(appended);
+        std::mem::swap(&mut self.scratch, scratch);
+
+        Ok(match consumed? {
+            Consumed::Borrowed(bytes) => {
+                self.record(bytes);
+                Reference::Borrowed(bytes)
+            }
+            Consumed::Copied(address, len) => {
+                let start = address - scratch.as_ptr() as usize;
+                Reference::Copied(&scratch[start..start + len])
+            }
+        })
+    }
+}
+
+/// The bytes consumed through a [`Tee`], split into chunks wherever reading was interrupted by
+/// [`Error::Pending`].
+///
+/// It is written with [`Display`] as one line per chunk, escaped like
+/// [`<[u8]>::escape_ascii`](slice::escape_ascii), and read back with [`FromStr`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Transcript {
+    pub chunks: Vec<Vec<u8>>,
+}
+
+impl Transcript {
+    /// Returns the chunks, to be replayed with [`InteruptSlice`](crate::read::InteruptSlice).
+    pub fn slices(&self) -> Vec<&[u8]> {
+        self.chunks.iter().map(Vec::as_slice).collect()
+    }
+}
+
+impl Display for Transcript {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for chunk in &self.chunks {
+            writeln!(f, "{}", chunk.escape_ascii())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let chunks = s
+            .split_terminator('\n')
+            .enumerate()
+            .map(|(number, line)| {
+                unescape(line.strip_suffix('\r').unwrap_or(line)).ok_or_else(|| {
+                    Error::Syntax(format!("line {}: invalid escape in {line:?}", number + 1))
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(Transcript { chunks })
+    }
+}
+
+/// Reverses [`<[u8]>::escape_ascii`](slice::escape_ascii).
+fn unescape(line: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(line.len());
+    let mut chars = line.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        bytes.push(match chars.next()? {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'0' => b'\0',
+            byte @ (b'\\' | b'\'' | b'"') => byte,
+            b'x' => {
+                let hex = [chars.next()?, chars.next()?];
+                u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?
+            }
+            _ => return None,
+        });
+    }
+    Some(bytes)
+}
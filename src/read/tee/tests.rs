@@ -0,0 +1,94 @@
+use super::{Tee, Transcript};
+use crate::{
+    read::{InteruptSlice, Read},
+    Error, RequestDeserializer, Value,
+};
+use serde::Deserialize;
+
+/// Deserializes a `Value`, retrying on `Pending`, and returns it with the number of retries.
+fn deserialize<'de, R: Read<'de>>(de: &mut RequestDeserializer<'de, R>) -> (Value, usize) {
+    let mut pending = 0;
+    loop {
+        match Value::deserialize(&mut *de) {
+            Err(Error::Pending) => pending += 1,
+            result => return (result.unwrap(), pending),
+        }
+    }
+}
+
+const CHUNKS: [&[u8]; 4] = [
+    b"file: a.flac\nTi",
+    b"tle: T\nbinary: 2\n\xff",
+    b"\n\n",
+    b"OK\nfile: b.flac\nOK\n",
+];
+
+#[test]
+fn record_and_replay() {
+    let src: &mut [&[u8]] = &mut CHUNKS.clone();
+    let mut de = RequestDeserializer::from_read(Tee::new(InteruptSlice { src }));
+    let (value, pending) = deserialize(&mut de);
+    assert_eq!(pending, 3);
+    let (_, transcript) = de.into_inner().into_inner();
+    assert_eq!(
+        transcript.chunks,
+        [
+            b"file: a.flac\nTi".as_slice(),
+            b"tle: T\nbinary: 2\n\xff",
+            b"\n\n",
+            b"OK\n",
+        ]
+    );
+
+    let mut slices = transcript.slices();
+    let mut de = RequestDeserializer::from_read(InteruptSlice { src: &mut slices });
+    assert_eq!(deserialize(&mut de), (value, pending));
+}
+
+#[test]
+fn format() {
+    let transcript = Transcript {
+        chunks: vec![
+            b"file: a\tb\nbinary: 2\n".to_vec(),
+            Vec::new(),
+            b"\xff\\\"\n".to_vec(),
+        ],
+    };
+    let text = transcript.to_string();
+    assert_eq!(text, "file: a\\tb\\nbinary: 2\\n\n\n\\xff\\\\\\\"\\n\n");
+    assert_eq!(text.parse::<Transcript>(), Ok(transcript));
+
+    assert!("\\q\n".parse::<Transcript>().unwrap_err().is_syntax());
+    assert!("\\xf\n".parse::<Transcript>().unwrap_err().is_syntax());
+}
+
+#[test]
+fn discard_without_peek() {
+    let src: &mut [&[u8]] = &mut [b"ab", b"", b"c"];
+    let mut tee = Tee::new(InteruptSlice { src });
+    tee.discard();
+    assert_eq!(tee.next(), Ok(Some(b'b')));
+    // Skips the ends of both chunks, like `discard` itself would.
+    tee.discard();
+    tee.discard();
+    tee.discard();
+    let (_, transcript) = tee.into_inner();
+    assert_eq!(transcript.chunks, [b"ab".as_slice(), b"", b"c"]);
+}
+
+#[test]
+fn many_chunks() {
+    let data = [7; 1000];
+    let mut chunks: Vec<&[u8]> = vec![b"binary: 1000\n"];
+    chunks.extend(data.chunks(3));
+    chunks.push(b"\nOK\n");
+    let src: &mut [&[u8]] = &mut chunks.clone();
+    let mut de = RequestDeserializer::from_read(Tee::new(InteruptSlice { src }));
+    let (value, _) = deserialize(&mut de);
+    assert_eq!(
+        value.0[0].get("binary"),
+        Some(&crate::Field::Binary(data.to_vec()))
+    );
+    let (_, transcript) = de.into_inner().into_inner();
+    assert_eq!(transcript.chunks.concat(), chunks.concat());
+}
@@ -0,0 +1,28 @@
+pub use self::{command::CommandSerializer, response::ResponseSerializer};
+
+mod command;
+mod part;
+mod response;
+
+/// Implements serializer methods that fail, because `$what` can't be serialized from them.
+///
+/// Methods returning a compound serializer name its associated type after `->`.
+macro_rules! unsupported {
+    (@ok) => { Self::Ok };
+    (@ok $ok:ident) => { Self::$ok };
+    ($what:literal; $($method:ident$(<$generic:ident>)?($($ty:ty),*) $(-> $ok:ident)?)*) => {
+        $(
+            fn $method$(<$generic: ?Sized + serde::Serialize>)?(
+                self,
+                $(_: $ty),*
+            ) -> Result<unsupported!(@ok $($ok)?), Self::Error> {
+                Err(Error::Custom(format!(
+                    "can't serialize {} as {}",
+                    stringify!($method).trim_start_matches("serialize_"),
+                    $what,
+                )))
+            }
+        )*
+    };
+}
+pub(crate) use unsupported;
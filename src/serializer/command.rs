@@ -0,0 +1,270 @@
+use self::arguments::ArgumentSerializer;
+use crate::{serializer::unsupported, Error};
+use serde::{
+    ser::{Impossible, SerializeSeq, SerializeStructVariant, SerializeTupleVariant},
+    Serialize, Serializer,
+};
+use std::io;
+
+mod arguments;
+
+#[cfg(test)]
+mod tests;
+
+/// Names of the commands that are followed by a list of commands up to `command_list_end`.
+const COMMAND_LIST_BEGIN: [&str; 2] = ["command_list_begin", "command_list_ok_begin"];
+
+/// Serializes enums as commands, so they can be read back by
+/// [`CommandDeserializer`](crate::CommandDeserializer).
+///
+/// The variant is written as the name of the command, followed by its fields as arguments.
+/// `None` arguments are left out, sequences are written as one argument per element and tuples
+/// as a single argument joined by `:`, e.g. a range like `0:5`. Arguments are quoted and escaped
+/// where necessary.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct CommandSerializer<W> {
+    write: W,
+}
+
+impl<W: io::Write> CommandSerializer<W> {
+    pub fn from_write(write: W) -> Self {
+        CommandSerializer { write }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.write
+    }
+
+    /// Writes the command `name`, which is only known at runtime, followed by `arguments`, which
+    /// are serialized like the content of a newtype variant: a sequence, like a slice of strings,
+    /// as one argument per element.
+    pub fn command<T>(&mut self, name: &str, arguments: &T) -> crate::Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let invalid = |c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\');
+        if name.is_empty() || name.contains(invalid) {
+            return Err(Error::Custom(format!("invalid command name {name:?}")));
+        }
+
+        let mut line = name.into();
+        arguments.serialize(ArgumentSerializer::new(&mut line))?;
+        self.line(line)
+    }
+
+    fn line(&mut self, mut line: Vec<u8>) -> crate::Result<()> {
+        line.push(b'\n');
+        self.write.write_all(&line).map_err(Error::from)
+    }
+}
+
+impl<'a, W: io::Write> Serializer for &'a mut CommandSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Command<'a, W>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Command<'a, W>;
+
+    unsupported! {
+        "a command";
+        serialize_bool(bool) serialize_i8(i8) serialize_i16(i16) serialize_i32(i32)
+        serialize_i64(i64) serialize_u8(u8) serialize_u16(u16) serialize_u32(u32)
+        serialize_u64(u64) serialize_f32(f32) serialize_f64(f64) serialize_char(char)
+        serialize_str(&str) serialize_bytes(&[u8]) serialize_none() serialize_unit()
+        serialize_unit_struct(&'static str)
+        serialize_seq(Option<usize>) -> SerializeSeq
+        serialize_tuple(usize) -> SerializeTuple
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_map(Option<usize>) -> SerializeMap
+        serialize_struct(&'static str, usize) -> SerializeStruct
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.line(variant.into())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if COMMAND_LIST_BEGIN.contains(&variant) {
+            self.line(variant.into())?;
+            return value.serialize(CommandListSerializer { ser: self });
+        }
+
+        let mut line = variant.into();
+        value.serialize(ArgumentSerializer::new(&mut line))?;
+        self.line(line)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(Command {
+            ser: self,
+            line: variant.into(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(Command {
+            ser: self,
+            line: variant.into(),
+        })
+    }
+}
+
+/// Collects the arguments of a command into a single line.
+pub struct Command<'a, W> {
+    ser: &'a mut CommandSerializer<W>,
+    line: Vec<u8>,
+}
+
+impl<W: io::Write> SerializeTupleVariant for Command<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(ArgumentSerializer::new(&mut self.line))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.line(self.line)
+    }
+}
+
+impl<W: io::Write> SerializeStructVariant for Command<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, _: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(ArgumentSerializer::new(&mut self.line))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.line(self.line)
+    }
+}
+
+/// Writes the commands of a command list, followed by `command_list_end`.
+struct CommandListSerializer<'a, W> {
+    ser: &'a mut CommandSerializer<W>,
+}
+
+impl<'a, W: io::Write> Serializer for CommandListSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "a command list";
+        serialize_bool(bool) serialize_i8(i8) serialize_i16(i16) serialize_i32(i32)
+        serialize_i64(i64) serialize_u8(u8) serialize_u16(u16) serialize_u32(u32)
+        serialize_u64(u64) serialize_f32(f32) serialize_f64(f64) serialize_char(char)
+        serialize_str(&str) serialize_bytes(&[u8]) serialize_none() serialize_unit()
+        serialize_unit_struct(&'static str)
+        serialize_unit_variant(&'static str, u32, &'static str)
+        serialize_newtype_variant<T>(&'static str, u32, &'static str, &T)
+        serialize_tuple(usize) -> SerializeTuple
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> SerializeTupleVariant
+        serialize_map(Option<usize>) -> SerializeMap
+        serialize_struct(&'static str, usize) -> SerializeStruct
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> SerializeStructVariant
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<W: io::Write> SerializeSeq for CommandListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.line(b"command_list_end".to_vec())
+    }
+}
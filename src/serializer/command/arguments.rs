@@ -0,0 +1,183 @@
+use crate::{
+    serializer::{
+        part::{Joined, PartSerializer},
+        unsupported,
+    },
+    Error,
+};
+use serde::{
+    ser::{Impossible, SerializeSeq, SerializeTuple},
+    Serialize, Serializer,
+};
+
+/// Appends arguments to the line of a command.
+///
+/// `None` and unit append nothing, sequences append one argument per element and tuples a single
+/// argument with their elements joined by `:`.
+pub(super) struct ArgumentSerializer<'a> {
+    line: &'a mut Vec<u8>,
+}
+
+impl<'a> ArgumentSerializer<'a> {
+    pub(super) fn new(line: &'a mut Vec<u8>) -> Self {
+        ArgumentSerializer { line }
+    }
+
+    /// Appends `argument`, quoting it if it is empty or contains whitespace, quotes or backslashes.
+    fn push(self, argument: &[u8]) -> crate::Result<()> {
+        if argument.contains(&b'\n') {
+            return Err(Error::Custom(format!(
+                "argument {:?} must not contain newlines",
+                String::from_utf8_lossy(argument)
+            )));
+        }
+
+        self.line.push(b' ');
+        let special = |byte: &u8| matches!(byte, b' ' | b'\t' | b'\r' | b'"' | b'\'' | b'\\');
+        if !argument.is_empty() && !argument.iter().any(special) {
+            self.line.extend_from_slice(argument);
+            return Ok(());
+        }
+
+        self.line.push(b'"');
+        for byte in argument {
+            if matches!(byte, b'"' | b'\\') {
+                self.line.push(b'\\');
+            }
+            self.line.push(*byte);
+        }
+        self.line.push(b'"');
+        Ok(())
+    }
+}
+
+macro_rules! serialize_part {
+    ($($method:ident($ty:ty))*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                match PartSerializer.$method(value)? {
+                    Some(part) => self.push(&part),
+                    None => Ok(()),
+                }
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for ArgumentSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Parts<'a>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "an argument";
+        serialize_newtype_variant<T>(&'static str, u32, &'static str, &T)
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> SerializeTupleVariant
+        serialize_map(Option<usize>) -> SerializeMap
+        serialize_struct(&'static str, usize) -> SerializeStruct
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> SerializeStructVariant
+    }
+
+    serialize_part! {
+        serialize_bool(bool) serialize_i8(i8) serialize_i16(i16) serialize_i32(i32)
+        serialize_i64(i64) serialize_u8(u8) serialize_u16(u16) serialize_u32(u32)
+        serialize_u64(u64) serialize_f32(f32) serialize_f64(f64) serialize_char(char)
+        serialize_str(&str) serialize_bytes(&[u8])
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.push(variant.as_bytes())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(Parts {
+            arg: self,
+            parts: Joined::default(),
+        })
+    }
+}
+
+impl SerializeSeq for ArgumentSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(ArgumentSerializer::new(self.line))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Joins the elements of a tuple by `:` into a single argument.
+pub(super) struct Parts<'a> {
+    arg: ArgumentSerializer<'a>,
+    parts: Joined,
+}
+
+impl SerializeTuple for Parts<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.parts.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.arg.push(&self.parts.into_inner())
+    }
+}
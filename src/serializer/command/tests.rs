@@ -0,0 +1,107 @@
+use crate::{read::Slice, CommandDeserializer, CommandSerializer, Error};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Command {
+    Status,
+    Play(Option<u32>),
+    Find(String, Option<String>, Option<(u32, Option<u32>)>),
+    Add { uri: String, position: Option<i32> },
+    Tagtypes(String, Vec<String>),
+    CommandListOkBegin(Vec<Command>),
+}
+
+fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    value.serialize(&mut ser)?;
+    Ok(ser.into_inner())
+}
+
+fn round_trip<'de, T>(value: &T, expected: &'de [u8])
+where
+    T: Serialize + Deserialize<'de> + PartialEq + Debug,
+{
+    let bytes = serialize(value).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&bytes),
+        String::from_utf8_lossy(expected)
+    );
+
+    let mut de = CommandDeserializer::from_read(Slice { src: expected });
+    assert_eq!(&T::deserialize(&mut de).unwrap(), value);
+}
+
+#[test]
+fn round_trip_commands() {
+    round_trip(&Command::Status, b"status\n");
+    round_trip(&Command::Play(None), b"play\n");
+    round_trip(&Command::Play(Some(2)), b"play 2\n");
+    round_trip(
+        &Command::Add {
+            uri: "a b.flac".to_string(),
+            position: Some(-1),
+        },
+        b"add \"a b.flac\" -1\n",
+    );
+    round_trip(
+        &Command::Tagtypes("enable".to_string(), vec!["Artist".into(), "Album".into()]),
+        b"tagtypes enable Artist Album\n",
+    );
+}
+
+#[test]
+fn round_trip_escaped() {
+    round_trip(
+        &Command::Find(
+            "(Artist == \"x\\y\")".to_string(),
+            Some("window".to_string()),
+            Some((0, Some(5))),
+        ),
+        b"find \"(Artist == \\\"x\\\\y\\\")\" window 0:5\n",
+    );
+    round_trip(
+        &Command::Find(String::new(), Some("window".to_string()), Some((3, None))),
+        b"find \"\" window 3:\n",
+    );
+}
+
+#[test]
+fn round_trip_command_list() {
+    round_trip(
+        &Command::CommandListOkBegin(vec![Command::Play(Some(1)), Command::Status]),
+        b"command_list_ok_begin\nplay 1\nstatus\ncommand_list_end\n",
+    );
+}
+
+#[test]
+fn newline_in_argument() {
+    assert_eq!(
+        serialize(&Command::Find("a\nb".to_string(), None, None)),
+        Err(Error::Custom(
+            "argument \"a\\nb\" must not contain newlines".to_string()
+        ))
+    );
+}
+
+#[test]
+fn runtime_name() {
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    ser.command("find", &["(Artist == \"A B\")", "sort", "Title"][..])
+        .unwrap();
+    ser.command("status", &()).unwrap();
+    assert_eq!(
+        ser.into_inner(),
+        b"find \"(Artist == \\\"A B\\\")\" sort Title\nstatus\n"
+    );
+
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    assert_eq!(
+        ser.command("play 1", &()),
+        Err(Error::Custom("invalid command name \"play 1\"".to_string()))
+    );
+    assert!(ser.command("", &()).is_err());
+    assert!(ser.into_inner().is_empty());
+}
@@ -0,0 +1,116 @@
+use crate::{serializer::unsupported, Error};
+use serde::{ser::Impossible, Serialize, Serializer};
+
+/// Joins the elements of a tuple by `:`, the way MPD writes ranges and audio formats like
+/// `44100:16:2`.
+#[derive(Default)]
+pub(super) struct Joined(Option<Vec<u8>>);
+
+impl Joined {
+    pub(super) fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> crate::Result<()> {
+        let part = value.serialize(PartSerializer)?.unwrap_or_default();
+        match &mut self.0 {
+            Some(parts) => {
+                parts.push(b':');
+                parts.extend(part);
+            }
+            None => self.0 = Some(part),
+        }
+        Ok(())
+    }
+
+    pub(super) fn into_inner(self) -> Vec<u8> {
+        self.0.unwrap_or_default()
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty))*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Some(value.to_string().into_bytes()))
+            }
+        )*
+    };
+}
+
+/// Serializes a single scalar value, or `None` if it is absent.
+pub(super) struct PartSerializer;
+
+impl Serializer for PartSerializer {
+    type Ok = Option<Vec<u8>>;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Impossible<Self::Ok, Error>;
+    type SerializeStruct = Impossible<Self::Ok, Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    unsupported! {
+        "a part of an argument";
+        serialize_newtype_variant<T>(&'static str, u32, &'static str, &T)
+        serialize_seq(Option<usize>) -> SerializeSeq
+        serialize_tuple(usize) -> SerializeTuple
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> SerializeTupleVariant
+        serialize_map(Option<usize>) -> SerializeMap
+        serialize_struct(&'static str, usize) -> SerializeStruct
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> SerializeStructVariant
+    }
+
+    serialize_display! {
+        serialize_i8(i8) serialize_i16(i16) serialize_i32(i32) serialize_i64(i64)
+        serialize_u8(u8) serialize_u16(u16) serialize_u32(u32) serialize_u64(u64)
+        serialize_f32(f32) serialize_f64(f64) serialize_char(char) serialize_str(&str)
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(vec![if value { b'1' } else { b'0' }]))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(value.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+}
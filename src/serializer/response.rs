@@ -0,0 +1,284 @@
+use self::value::{FieldSerializer, KeySerializer};
+use crate::{serializer::unsupported, version::split_field, Ack, Error};
+use serde::{
+    ser::{Impossible, SerializeMap, SerializeSeq, SerializeStruct},
+    Serialize, Serializer,
+};
+use std::{fmt::Display, io};
+
+mod value;
+
+#[cfg(test)]
+mod tests;
+
+/// Serializes responses in the format a server sends them, so they can be read back by
+/// [`RequestDeserializer`](crate::RequestDeserializer).
+///
+/// Structs and maps are written as `key: value` lines, sequences of them as consecutive records.
+/// Sequences inside of a record are written as repeated keys and bytes (as serialized by
+/// [`Serializer::serialize_bytes`]) as `binary: <length>`, followed by the data.
+/// Every response is terminated by `OK`, unless it is written as an [`Ack`] instead.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ResponseSerializer<W> {
+    write: W,
+}
+
+impl<W: io::Write> ResponseSerializer<W> {
+    pub fn from_write(write: W) -> Self {
+        ResponseSerializer { write }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.write
+    }
+
+    /// Writes `ack` in place of a response.
+    pub fn ack(&mut self, ack: &Ack) -> crate::Result<()> {
+        writeln!(self.write, "{ack}").map_err(Error::from)
+    }
+
+    fn pair(&mut self, key: &str, value: impl Display) -> crate::Result<()> {
+        if key.contains([':', '\n']) {
+            return Err(Error::Custom(format!(
+                "key {key:?} must not contain `:` or newlines"
+            )));
+        }
+        writeln!(self.write, "{key}: {value}").map_err(Error::from)
+    }
+
+    fn binary(&mut self, data: &[u8]) -> crate::Result<()> {
+        writeln!(self.write, "binary: {}", data.len())
+            .and_then(|()| self.write.write_all(data))
+            .and_then(|()| self.write.write_all(b"\n"))
+            .map_err(Error::from)
+    }
+
+    fn ok(&mut self) -> crate::Result<()> {
+        self.write.write_all(b"OK\n").map_err(Error::from)
+    }
+}
+
+impl<'a, W: io::Write> Serializer for &'a mut ResponseSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Records<'a, W>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Record<'a, W>;
+    type SerializeStruct = Record<'a, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "a response";
+        serialize_bool(bool) serialize_i8(i8) serialize_i16(i16) serialize_i32(i32)
+        serialize_i64(i64) serialize_u8(u8) serialize_u16(u16) serialize_u32(u32)
+        serialize_u64(u64) serialize_f32(f32) serialize_f64(f64) serialize_char(char)
+        serialize_str(&str) serialize_bytes(&[u8])
+        serialize_unit_variant(&'static str, u32, &'static str)
+        serialize_newtype_variant<T>(&'static str, u32, &'static str, &T)
+        serialize_tuple(usize) -> SerializeTuple
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> SerializeTupleVariant
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> SerializeStructVariant
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.ok()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.ok()
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.ok()
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(Records { ser: self })
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Record::new(self, true))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(Record::new(self, true))
+    }
+}
+
+/// Writes the fields of a struct or map as `key: value` lines.
+pub struct Record<'a, W> {
+    ser: &'a mut ResponseSerializer<W>,
+    key: Option<String>,
+    /// Whether the record makes up the whole response and has to be terminated by `OK`.
+    ok: bool,
+}
+
+impl<'a, W: io::Write> Record<'a, W> {
+    fn new(ser: &'a mut ResponseSerializer<W>, ok: bool) -> Self {
+        Record { ser, key: None, ok }
+    }
+
+    fn end(self) -> crate::Result<()> {
+        if self.ok {
+            self.ser.ok()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<W: io::Write> SerializeStruct for Record<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let (key, _) = split_field(key);
+        value.serialize(FieldSerializer::new(self.ser, key))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Record::end(self)
+    }
+}
+
+impl<W: io::Write> SerializeMap for Record<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        value.serialize(FieldSerializer::new(self.ser, &key))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Record::end(self)
+    }
+}
+
+/// Writes a sequence of records, terminated by a single `OK`.
+pub struct Records<'a, W> {
+    ser: &'a mut ResponseSerializer<W>,
+}
+
+impl<W: io::Write> SerializeSeq for Records<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(RecordSerializer { ser: self.ser })
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.ok()
+    }
+}
+
+/// Serializes a single element of a sequence of records.
+struct RecordSerializer<'a, W> {
+    ser: &'a mut ResponseSerializer<W>,
+}
+
+impl<'a, W: io::Write> Serializer for RecordSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Record<'a, W>;
+    type SerializeStruct = Record<'a, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "a record";
+        serialize_bool(bool) serialize_i8(i8) serialize_i16(i16) serialize_i32(i32)
+        serialize_i64(i64) serialize_u8(u8) serialize_u16(u16) serialize_u32(u32)
+        serialize_u64(u64) serialize_f32(f32) serialize_f64(f64) serialize_char(char)
+        serialize_str(&str) serialize_bytes(&[u8]) serialize_none() serialize_unit()
+        serialize_unit_struct(&'static str)
+        serialize_unit_variant(&'static str, u32, &'static str)
+        serialize_newtype_variant<T>(&'static str, u32, &'static str, &T)
+        serialize_seq(Option<usize>) -> SerializeSeq
+        serialize_tuple(usize) -> SerializeTuple
+        serialize_tuple_struct(&'static str, usize) -> SerializeTupleStruct
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> SerializeTupleVariant
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> SerializeStructVariant
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Record::new(self.ser, false))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(Record::new(self.ser, false))
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{read::Slice, Ack, Error, RequestDeserializer, ResponseSerializer};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Song {
+    file: String,
+    #[serde(rename = "Title", skip_serializing_if = "Option::is_none", default)]
+    title: Option<String>,
+    #[serde(rename = "Artist", default)]
+    artists: Vec<String>,
+    #[serde(rename = "Pos")]
+    pos: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Picture {
+    size: u64,
+    #[serde(rename = "type")]
+    mime: String,
+    #[serde(with = "serde_bytes")]
+    binary: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Status {
+    repeat: bool,
+    volume: Option<u8>,
+    elapsed: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Audio {
+    audio: (u32, String, u8),
+    range: Option<(f64, Option<f64>)>,
+}
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    value.serialize(&mut ser).unwrap();
+    ser.into_inner()
+}
+
+fn round_trip<'de, T>(value: &T, expected: &'de [u8])
+where
+    T: Serialize + Deserialize<'de> + PartialEq + Debug,
+{
+    let bytes = serialize(value);
+    assert_eq!(
+        String::from_utf8_lossy(&bytes),
+        String::from_utf8_lossy(expected)
+    );
+
+    let mut de = RequestDeserializer::from_read(Slice { src: expected });
+    assert_eq!(&T::deserialize(&mut de).unwrap(), value);
+}
+
+#[test]
+fn struct_round_trip() {
+    round_trip(
+        &Song {
+            file: "a.flac".to_string(),
+            title: Some("A".to_string()),
+            artists: vec!["B".to_string(), "C".to_string()],
+            pos: 0,
+        },
+        b"file: a.flac\nTitle: A\nArtist: B\nArtist: C\nPos: 0\nOK\n",
+    );
+}
+
+#[test]
+fn records_round_trip() {
+    round_trip(
+        &vec![
+            Song {
+                file: "a.flac".to_string(),
+                title: None,
+                artists: vec![],
+                pos: 0,
+            },
+            Song {
+                file: "b.flac".to_string(),
+                title: Some("B".to_string()),
+                artists: vec!["C".to_string()],
+                pos: 1,
+            },
+        ],
+        b"file: a.flac\nPos: 0\nfile: b.flac\nTitle: B\nArtist: C\nPos: 1\nOK\n",
+    );
+    round_trip::<Vec<Song>>(&vec![], b"OK\n");
+}
+
+#[test]
+fn values_round_trip() {
+    round_trip(
+        &Status {
+            repeat: true,
+            volume: None,
+            elapsed: 1.5,
+        },
+        b"repeat: 1\nelapsed: 1.5\nOK\n",
+    );
+    round_trip(&(), b"OK\n");
+    round_trip::<Option<Status>>(&None, b"OK\n");
+}
+
+#[test]
+fn tuple_round_trip() {
+    round_trip(
+        &Audio {
+            audio: (44100, "f".to_string(), 2),
+            range: Some((1.5, None)),
+        },
+        b"audio: 44100:f:2\nrange: 1.5:\nOK\n",
+    );
+}
+
+#[test]
+fn binary_round_trip() {
+    round_trip(
+        &Picture {
+            size: 4,
+            mime: "image/png".to_string(),
+            binary: b"\x89P\nG".to_vec(),
+        },
+        b"size: 4\ntype: image/png\nbinary: 4\n\x89P\nG\nOK\n",
+    );
+}
+
+#[test]
+fn ack() {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    let ack = Ack {
+        code: 50,
+        index: 1,
+        command: "play".to_string(),
+        message: "No such song".to_string(),
+    };
+    ser.ack(&ack).unwrap();
+    let bytes = ser.into_inner();
+    assert_eq!(bytes, b"ACK [50@1] {play} No such song\n");
+
+    let mut de = RequestDeserializer::from_read(Slice { src: &bytes });
+    assert_eq!(Song::deserialize(&mut de), Err(Error::Ack(ack)));
+}
+
+#[test]
+fn newline_in_value() {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    let song = Song {
+        file: "a\nOK".to_string(),
+        title: None,
+        artists: vec![],
+        pos: 0,
+    };
+    assert_eq!(
+        song.serialize(&mut ser),
+        Err(Error::Custom(
+            "value of \"file\" must not contain newlines".to_string()
+        ))
+    );
+}
+
+#[test]
+fn newline_char() {
+    #[derive(Serialize)]
+    struct Separator {
+        separator: char,
+    }
+
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    assert_eq!(
+        Separator { separator: '\n' }.serialize(&mut ser),
+        Err(Error::Custom(
+            "value of \"separator\" must not contain newlines".to_string()
+        ))
+    );
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    Separator { separator: ';' }.serialize(&mut ser).unwrap();
+    assert_eq!(ser.into_inner(), b"separator: ;\nOK\n");
+}
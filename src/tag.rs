@@ -0,0 +1,126 @@
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod tests;
+
+macro_rules! tags {
+    ($($(#[$meta:meta])* $variant:ident => $name:literal,)*) => {
+        /// A tag type, as used as key in responses, in `tagtypes` and as argument of commands like
+        /// `list`.
+        ///
+        /// Tag names are matched ignoring ASCII case, names of unknown tags are kept as
+        /// [`Tag::Other`].
+        #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+        pub enum Tag {
+            $($(#[$meta])* $variant,)*
+            Other(String),
+        }
+
+        impl Tag {
+            /// All known tags, without [`Tag::Other`].
+            pub const ALL: &'static [Tag] = &[$(Tag::$variant,)*];
+
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Tag::$variant => $name,)*
+                    Tag::Other(name) => name,
+                }
+            }
+        }
+    };
+}
+
+tags! {
+    Artist => "Artist",
+    ArtistSort => "ArtistSort",
+    Album => "Album",
+    AlbumSort => "AlbumSort",
+    AlbumArtist => "AlbumArtist",
+    AlbumArtistSort => "AlbumArtistSort",
+    Title => "Title",
+    TitleSort => "TitleSort",
+    Track => "Track",
+    Name => "Name",
+    Genre => "Genre",
+    Mood => "Mood",
+    Date => "Date",
+    OriginalDate => "OriginalDate",
+    Composer => "Composer",
+    ComposerSort => "ComposerSort",
+    Performer => "Performer",
+    Conductor => "Conductor",
+    Work => "Work",
+    Ensemble => "Ensemble",
+    Movement => "Movement",
+    MovementNumber => "MovementNumber",
+    ShowMovement => "ShowMovement",
+    Location => "Location",
+    Grouping => "Grouping",
+    Comment => "Comment",
+    Disc => "Disc",
+    Label => "Label",
+    MusicBrainzArtistId => "MUSICBRAINZ_ARTISTID",
+    MusicBrainzAlbumId => "MUSICBRAINZ_ALBUMID",
+    MusicBrainzAlbumArtistId => "MUSICBRAINZ_ALBUMARTISTID",
+    MusicBrainzTrackId => "MUSICBRAINZ_TRACKID",
+    MusicBrainzReleaseTrackId => "MUSICBRAINZ_RELEASETRACKID",
+    MusicBrainzReleaseGroupId => "MUSICBRAINZ_RELEASEGROUPID",
+    MusicBrainzWorkId => "MUSICBRAINZ_WORKID",
+}
+
+impl FromStr for Tag {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = Tag::ALL
+            .iter()
+            .find(|tag| tag.as_str().eq_ignore_ascii_case(s))
+            .cloned();
+        Ok(tag.unwrap_or_else(|| Tag::Other(s.to_string())))
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagVisitor;
+
+        impl Visitor<'_> for TagVisitor {
+            type Value = Tag;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a tag name")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let Ok(tag) = v.parse();
+                Ok(tag)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                match std::str::from_utf8(v) {
+                    Ok(v) => self.visit_str(v),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(TagVisitor)
+    }
+}
@@ -0,0 +1,71 @@
+use super::Tag;
+use crate::{read::Slice, CommandSerializer, RequestDeserializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[test]
+fn parse() {
+    assert_eq!("Artist".parse(), Ok(Tag::Artist));
+    assert_eq!("albumartist".parse(), Ok(Tag::AlbumArtist));
+    assert_eq!("MUSICBRAINZ_TRACKID".parse(), Ok(Tag::MusicBrainzTrackId));
+    assert_eq!("Foo".parse(), Ok(Tag::Other("Foo".to_string())));
+    for tag in Tag::ALL {
+        assert_eq!(tag.as_str().parse().as_ref(), Ok(tag));
+    }
+}
+
+#[test]
+fn keys() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"Artist: A\nArtist: B\nTitle: T\nFoo: F\nOK\n",
+    });
+    assert_eq!(
+        BTreeMap::<Tag, Vec<String>>::deserialize(&mut de),
+        Ok(BTreeMap::from([
+            (Tag::Artist, vec!["A".to_string(), "B".to_string()]),
+            (Tag::Title, vec!["T".to_string()]),
+            (Tag::Other("Foo".to_string()), vec!["F".to_string()]),
+        ]))
+    );
+}
+
+#[test]
+fn tagtypes() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct TagTypes {
+        tagtype: Vec<Tag>,
+    }
+
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"tagtype: Artist\ntagtype: MUSICBRAINZ_WORKID\nOK\n",
+    });
+    assert_eq!(
+        TagTypes::deserialize(&mut de),
+        Ok(TagTypes {
+            tagtype: vec![Tag::Artist, Tag::MusicBrainzWorkId]
+        })
+    );
+}
+
+#[test]
+fn arguments() {
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Request {
+        List(Tag),
+        Tagtypes(&'static str, Vec<Tag>),
+    }
+
+    let mut ser = CommandSerializer::from_write(Vec::new());
+    Request::List(Tag::AlbumArtist).serialize(&mut ser).unwrap();
+    Request::Tagtypes(
+        "enable",
+        vec![Tag::Artist, Tag::Other("Foo Bar".to_string())],
+    )
+    .serialize(&mut ser)
+    .unwrap();
+    assert_eq!(
+        ser.into_inner(),
+        b"list AlbumArtist\ntagtypes enable Artist \"Foo Bar\"\n"
+    );
+}
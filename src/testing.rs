@@ -0,0 +1,195 @@
+//! A mock server for testing clients without a real one.
+//!
+//! A [`Script`] lists the commands a client is expected to send along with the responses to
+//! them. It can be built in code or parsed from a transcript like
+//!
+//! ```text
+//! < OK MPD 0.23.5
+//! > status
+//! < volume: 50
+//! < OK
+//! > albumart a.flac 0
+//! < size: 3
+//! < binary: 3
+//! <x 89504e
+//! < OK
+//! > play 5
+//! < ACK [2@0] {play} Bad song index
+//! ```
+//!
+//! Lines starting with `>` are commands, lines starting with `<` are sent back as they are and
+//! lines starting with `<x` are hex encoded binary data, followed by the newline terminating it.
+//! Lines before the first command are the greeting, [`Script::DEFAULT_GREETING`] if there are none.
+//! Empty lines and lines starting with `#` are ignored.
+
+use crate::{client::Stream, Error};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    str::FromStr,
+    thread::{self, JoinHandle},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The commands a client is expected to send, with the responses to them.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Script {
+    greeting: Vec<u8>,
+    exchanges: Vec<(String, Vec<u8>)>,
+}
+
+impl Script {
+    pub const DEFAULT_GREETING: &'static str = "OK MPD 0.23.5\n";
+
+    pub fn new() -> Self {
+        Script {
+            greeting: Self::DEFAULT_GREETING.into(),
+            exchanges: Vec::new(),
+        }
+    }
+
+    /// Reads a transcript from `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Replaces the greeting, which has to include the trailing newline.
+    pub fn greeting(mut self, greeting: impl Into<Vec<u8>>) -> Self {
+        self.greeting = greeting.into();
+        self
+    }
+
+    /// Expects `command`, without the trailing newline, to be answered with `response`.
+    pub fn expect(mut self, command: impl Into<String>, response: impl Into<Vec<u8>>) -> Self {
+        self.exchanges.push((command.into(), response.into()));
+        self
+    }
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for Script {
+    type Err = Error;
+
+    /// Parses a transcript, as described in the [module documentation](self).
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let mut greeting = Vec::new();
+        let mut exchanges: Vec<(String, Vec<u8>)> = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let response = match exchanges.last_mut() {
+                Some((_, response)) => response,
+                None => &mut greeting,
+            };
+            if line.is_empty() || line.starts_with('#') {
+            } else if let Some(command) = line.strip_prefix("> ") {
+                exchanges.push((command.to_string(), Vec::new()));
+            } else if let Some(hex) = line.strip_prefix("<x ") {
+                response.extend(decode_hex(hex.trim()).ok_or_else(|| {
+                    Error::Syntax(format!("line {}: invalid hex {hex:?}", number + 1))
+                })?);
+                response.push(b'\n');
+            } else if let Some(data) = line.strip_prefix("< ") {
+                response.extend_from_slice(data.as_bytes());
+                response.push(b'\n');
+            } else {
+                return Err(Error::Syntax(format!(
+                    "line {}: expected `> `, `< ` or `<x `, found {line:?}",
+                    number + 1
+                )));
+            }
+        }
+
+        if greeting.is_empty() {
+            greeting = Self::DEFAULT_GREETING.into();
+        }
+        Ok(Script {
+            greeting,
+            exchanges,
+        })
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// A server running a [`Script`] for a single connection in a background thread.
+///
+/// An unexpected command is answered with an `ACK` of kind
+/// [`AckKind::Unknown`](crate::AckKind::Unknown), after which the connection is closed and
+/// [`MockServer::finish`] fails.
+#[derive(Debug)]
+pub struct MockServer {
+    handle: JoinHandle<crate::Result<()>>,
+}
+
+impl MockServer {
+    /// Runs `script` on one end of a pair of connected sockets and returns the other end.
+    pub fn pipe(script: Script) -> io::Result<(Stream, MockServer)> {
+        let (client, server) = UnixStream::pair()?;
+        let handle = thread::spawn(move || serve(server, &script));
+        Ok((Stream::Unix(client), MockServer { handle }))
+    }
+
+    /// Listens on a Unix socket at `path` and runs `script` for the first connection.
+    pub fn bind(path: impl AsRef<Path>, script: Script) -> io::Result<MockServer> {
+        let listener = UnixListener::bind(path)?;
+        let handle = thread::spawn(move || serve(listener.accept()?.0, &script));
+        Ok(MockServer { handle })
+    }
+
+    /// Waits for the connection to be closed after the whole script ran and fails if it didn't.
+    pub fn finish(self) -> crate::Result<()> {
+        self.handle
+            .join()
+            .unwrap_or_else(|_| Err(Error::Custom("mock server panicked".to_string())))
+    }
+}
+
+/// Runs `script` on `stream`, until the client closes it.
+pub fn serve<S: io::Read + io::Write>(stream: S, script: &Script) -> crate::Result<()> {
+    let mut stream = BufReader::new(stream);
+    stream.get_mut().write_all(&script.greeting)?;
+
+    let mut line = Vec::new();
+    for (command, response) in &script.exchanges {
+        line.clear();
+        if stream.read_until(b'\n', &mut line)? == 0 {
+            return Err(Error::Custom(format!(
+                "expected {command:?}, but the connection was closed"
+            )));
+        }
+        let received = String::from_utf8_lossy(line.strip_suffix(b"\n").unwrap_or(&line));
+        if received != *command {
+            let name = received.split(' ').next().unwrap_or_default();
+            writeln!(stream.get_mut(), "ACK [5@0] {{{name}}} unexpected command")?;
+            return Err(Error::Custom(format!(
+                "expected {command:?}, received {received:?}"
+            )));
+        }
+        stream.get_mut().write_all(response)?;
+    }
+
+    line.clear();
+    match stream.read_until(b'\n', &mut line)? {
+        0 => Ok(()),
+        _ => Err(Error::Custom(format!(
+            "expected the connection to be closed, received {:?}",
+            String::from_utf8_lossy(&line)
+        ))),
+    }
+}
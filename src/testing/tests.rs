@@ -0,0 +1,118 @@
+use super::{MockServer, Script};
+use crate::{client::Client, AckKind, Error, Version};
+use serde::Deserialize;
+use std::{
+    io::{Read, Write},
+    process,
+};
+
+const TRANSCRIPT: &str = "\
+# Greeting of a newer server.
+< OK MPD 0.24.0
+
+> status
+< volume: 50
+< repeat: 0
+< random: 1
+< single: 0
+< consume: 0
+< playlist: 3
+< playlistlength: 0
+< state: stop
+< OK
+> albumart a.flac 0
+< size: 3
+< binary: 3
+<x 4f4b0a
+< OK
+> play 5
+< ACK [2@0] {play} Bad song index
+";
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Picture {
+    size: u64,
+    #[serde(with = "serde_bytes")]
+    binary: Vec<u8>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Command {
+    Albumart(&'static str, u32),
+}
+
+#[test]
+fn transcript() {
+    let (stream, server) = MockServer::pipe(TRANSCRIPT.parse().unwrap()).unwrap();
+    let mut client = Client::new(stream).unwrap();
+    assert_eq!(client.version(), Version::new(0, 24, 0));
+    assert_eq!(client.status().unwrap().volume, Some(50));
+    assert_eq!(
+        client
+            .command::<_, Picture>(&Command::Albumart("a.flac", 0))
+            .unwrap(),
+        Picture {
+            size: 3,
+            binary: b"OK\n".to_vec(),
+        }
+    );
+    match client.play(Some(5)) {
+        Err(Error::Ack(ack)) => assert_eq!(ack.kind(), AckKind::Arg),
+        result => panic!("expected an ack, found {result:?}"),
+    }
+    drop(client);
+    server.finish().unwrap();
+}
+
+#[test]
+fn unexpected_command() {
+    let script = Script::new().expect("status", "OK\n");
+    let (stream, server) = MockServer::pipe(script).unwrap();
+    let mut client = Client::new(stream).unwrap();
+    match client.current_song() {
+        Err(Error::Ack(ack)) => assert_eq!(ack.kind(), AckKind::Unknown),
+        result => panic!("expected an ack, found {result:?}"),
+    }
+    assert_eq!(
+        server.finish(),
+        Err(Error::Custom(
+            "expected \"status\", received \"currentsong\"".to_string()
+        ))
+    );
+}
+
+#[test]
+fn incomplete() {
+    let script = Script::new().expect("status", "OK\n");
+    let (stream, server) = MockServer::pipe(script).unwrap();
+    drop(Client::new(stream).unwrap());
+    assert!(server.finish().unwrap_err().is_data());
+}
+
+#[test]
+fn bind() {
+    let path = std::env::temp_dir().join(format!("serde_mpd-mock-{}.socket", process::id()));
+    let script = Script::new()
+        .greeting("OK MPD 0.21.0\n")
+        .expect("ping", "OK\n");
+    let server = MockServer::bind(&path, script).unwrap();
+
+    let mut stream = std::os::unix::net::UnixStream::connect(&path).unwrap();
+    stream.write_all(b"ping\n").unwrap();
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+    let mut received = String::new();
+    stream.read_to_string(&mut received).unwrap();
+    assert_eq!(received, "OK MPD 0.21.0\nOK\n");
+    server.finish().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalid_transcript() {
+    assert!("> status\nOK\n".parse::<Script>().unwrap_err().is_syntax());
+    assert!("> albumart a 0\n<x 4f4\n"
+        .parse::<Script>()
+        .unwrap_err()
+        .is_syntax());
+}
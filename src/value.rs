@@ -0,0 +1,190 @@
+use crate::RequestDeserializer;
+use serde::{
+    de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt::{self, Formatter};
+
+#[cfg(test)]
+mod tests;
+
+/// A response of unknown shape, as list of records.
+///
+/// Deserializing it from a [`RequestDeserializer`] splits the response into records the same way
+/// a sequence of structs is split, at every occurrence of the first key of the response.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Value(pub Vec<Record>);
+
+/// The `(key, value)` pairs of a single record, in the order they were received.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Record(pub Vec<(String, Field)>);
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Field {
+    Text(String),
+    /// Binary data, announced by a `binary: <length>` line.
+    Binary(Vec<u8>),
+}
+
+/// Deserializes `T` from `value`, exactly as if it was deserialized from the response `value` was
+/// deserialized from.
+pub fn from_value<'de, T: Deserialize<'de>>(value: &'de Value) -> crate::Result<T> {
+    T::deserialize(&mut RequestDeserializer::from_value(value))
+}
+
+impl Record {
+    /// Returns the first field with `key`.
+    pub fn get(&self, key: &str) -> Option<&Field> {
+        self.0
+            .iter()
+            .find_map(|(k, field)| (k == key).then_some(field))
+    }
+}
+
+impl Field {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Field::Text(text) => Some(text),
+            Field::Binary(_) => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Field::Text(text) => text.as_bytes(),
+            Field::Binary(data) => data,
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.0)
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, field)| (key, field)))
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Field::Text(text) => serializer.serialize_str(text),
+            Field::Binary(data) => serializer.serialize_bytes(data),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a list of records")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut records = Vec::new();
+                while let Some(record) = seq.next_element()? {
+                    records.push(record);
+                }
+                Ok(Value(records))
+            }
+        }
+
+        deserializer.deserialize_seq(ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Record {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RecordVisitor;
+
+        impl<'de> Visitor<'de> for RecordVisitor {
+            type Value = Record;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a record")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    let binary = key == "binary";
+                    fields.push((key, map.next_value_seed(FieldVisitor { binary })?));
+                }
+                Ok(Record(fields))
+            }
+        }
+
+        deserializer.deserialize_map(RecordVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FieldVisitor { binary: false }.deserialize(deserializer)
+    }
+}
+
+/// Visits text as [`Field::Text`] and bytes as [`Field::Binary`], if they are the value of a
+/// `binary` key or aren't valid UTF-8.
+struct FieldVisitor {
+    binary: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for FieldVisitor {
+    type Value = Field;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Field, D::Error> {
+        if self.binary {
+            deserializer.deserialize_byte_buf(self)
+        } else {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for FieldVisitor {
+    type Value = Field;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("text or binary data")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Field::Text(v.to_string()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Field::Text(v))
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        if self.binary {
+            return Ok(Field::Binary(v));
+        }
+        match String::from_utf8(v) {
+            Ok(text) => Ok(Field::Text(text)),
+            Err(err) => Ok(Field::Binary(err.into_bytes())),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut data = Vec::new();
+        while let Some(byte) = seq.next_element()? {
+            data.push(byte);
+        }
+        Ok(Field::Binary(data))
+    }
+}
@@ -0,0 +1,101 @@
+use super::{from_value, Field, Record, Value};
+use crate::{
+    read::{InteruptSlice, Slice},
+    Error, RequestDeserializer, ResponseSerializer,
+};
+use serde::{Deserialize, Serialize};
+
+fn text(key: &str, value: &str) -> (String, Field) {
+    (key.to_string(), Field::Text(value.to_string()))
+}
+
+fn songs() -> Value {
+    Value(vec![
+        Record(vec![
+            text("file", "a.flac"),
+            text("Artist", "A"),
+            text("Artist", "B"),
+        ]),
+        Record(vec![text("file", "b.flac"), text("Title", "T")]),
+    ])
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Song {
+    #[serde(rename = "file")]
+    file: String,
+    #[serde(default)]
+    artist: Vec<String>,
+    title: Option<String>,
+}
+
+#[test]
+fn records() {
+    let mut de = RequestDeserializer::from_read(Slice {
+        src: b"file: a.flac\nArtist: A\nArtist: B\nfile: b.flac\nTitle: T\nOK\n",
+    });
+    assert_eq!(Value::deserialize(&mut de), Ok(songs()));
+
+    let mut de = RequestDeserializer::from_read(Slice { src: b"OK\n" });
+    assert_eq!(Value::deserialize(&mut de), Ok(Value::default()));
+}
+
+#[test]
+fn binary() {
+    let src: &mut [&[u8]] = &mut [b"size: 4\nbinary: 2\n\xff", b"\n\nOK\n"];
+    let mut de = RequestDeserializer::from_read(InteruptSlice { src });
+    assert_eq!(Value::deserialize(&mut de), Err(Error::Pending));
+    let value = Value::deserialize(&mut de).unwrap();
+    assert_eq!(
+        value,
+        Value(vec![Record(vec![
+            text("size", "4"),
+            ("binary".to_string(), Field::Binary(b"\xff\n".to_vec())),
+        ])])
+    );
+    assert_eq!(value.0[0].get("binary").unwrap().as_bytes(), b"\xff\n");
+    assert_eq!(value.0[0].get("size").unwrap().as_str(), Some("4"));
+
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    value.serialize(&mut ser).unwrap();
+    assert_eq!(ser.into_inner(), b"size: 4\nbinary: 2\n\xff\n\nOK\n");
+}
+
+#[test]
+fn serialize() {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    songs().serialize(&mut ser).unwrap();
+    assert_eq!(
+        ser.into_inner(),
+        b"file: a.flac\nArtist: A\nArtist: B\nfile: b.flac\nTitle: T\nOK\n"
+    );
+}
+
+#[test]
+fn typed() {
+    let value = songs();
+    assert_eq!(
+        from_value::<Vec<Song>>(&value),
+        Ok(vec![
+            Song {
+                file: "a.flac".to_string(),
+                artist: vec!["A".to_string(), "B".to_string()],
+                title: None,
+            },
+            Song {
+                file: "b.flac".to_string(),
+                artist: Vec::new(),
+                title: Some("T".to_string()),
+            },
+        ])
+    );
+
+    let value = Value(vec![Record(vec![text("volume", "50")])]);
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Status {
+        volume: u8,
+    }
+    assert_eq!(from_value(&value), Ok(Status { volume: 50 }));
+    assert_eq!(from_value::<u8>(&value), Ok(50));
+}
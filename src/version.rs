@@ -0,0 +1,74 @@
+use crate::Error;
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A protocol version, as announced by the server in its greeting `OK MPD 0.23.5`.
+///
+/// Struct fields the server only sends since some version can be annotated with it, like
+/// `#[serde(rename = "elapsed@0.20")]`. If the version of the server is known to
+/// [`RequestDeserializer`](crate::RequestDeserializer), such a field missing from a response is
+/// an error for servers at least that version, and expected (making an `Option` field `None`) for
+/// older ones. The annotation isn't part of the key, neither when deserializing nor when
+/// serializing with this crate, but other formats see it as part of the field name. Fields of
+/// flattened structs must not be annotated.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Splits a struct field into its key and the version it is annotated with, if any.
+///
+/// The version is appended to the name of the field after an `@`, like `elapsed@0.20`, since serde
+/// passes nothing but the names of fields on to deserializers. A suffix that isn't a valid version
+/// is part of the key. [`ResponseSerializer`](crate::ResponseSerializer) strips the suffix from
+/// struct fields and [`RequestDeserializer`](crate::RequestDeserializer) matches keys without it,
+/// but only for structs they handle themselves. Fields of flattened structs are serialized and
+/// deserialized as map entries, whose keys are taken verbatim, so they must not be annotated.
+/// Other formats see the annotated name as well.
+pub(crate) fn split_field(field: &str) -> (&str, Option<Version>) {
+    match field.rsplit_once('@') {
+        Some((key, version)) => match version.parse() {
+            Ok(version) => (key, Some(version)),
+            Err(_) => (field, None),
+        },
+        None => (field, None),
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    /// Parses `major.minor` or `major.minor.patch`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.').map(|part| part.parse::<u32>().ok());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(major)), Some(Some(minor)), patch, None) if patch != Some(None) => {
+                Ok(Version::new(major, minor, patch.flatten().unwrap_or(0)))
+            }
+            _ => Err(Error::Syntax(format!("invalid version {s:?}"))),
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
@@ -0,0 +1,92 @@
+use super::{split_field, Version};
+use crate::{read::Slice, Error, RequestDeserializer, ResponseSerializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Status {
+    state: String,
+    #[serde(rename = "elapsed@0.20")]
+    elapsed: Option<f64>,
+    #[serde(
+        rename = "lastloadedplaylist@0.24",
+        skip_serializing_if = "Option::is_none"
+    )]
+    last_loaded_playlist: Option<String>,
+}
+
+fn deserialize(version: Option<Version>, src: &[u8]) -> crate::Result<Status> {
+    Status::deserialize(&mut RequestDeserializer::from_read(Slice { src }).version(version))
+}
+
+#[test]
+fn parse() {
+    assert_eq!("0.23.5".parse(), Ok(Version::new(0, 23, 5)));
+    assert_eq!("0.24".parse(), Ok(Version::new(0, 24, 0)));
+    assert_eq!(Version::new(0, 24, 0).to_string(), "0.24.0");
+    assert_eq!(
+        "0.24.".parse::<Version>(),
+        Err(Error::Syntax("invalid version \"0.24.\"".to_string()))
+    );
+    assert!("1".parse::<Version>().is_err());
+    assert_eq!(
+        split_field("elapsed@0.20"),
+        ("elapsed", Some(Version::new(0, 20, 0)))
+    );
+    assert_eq!(split_field("a@b"), ("a@b", None));
+}
+
+#[test]
+fn gated_fields() {
+    let src = b"state: play\nelapsed: 1.5\nOK\n";
+    let status = Status {
+        state: "play".to_string(),
+        elapsed: Some(1.5),
+        last_loaded_playlist: None,
+    };
+    assert_eq!(deserialize(None, src).as_ref(), Ok(&status));
+    assert_eq!(
+        deserialize(Some(Version::new(0, 23, 5)), src).as_ref(),
+        Ok(&status)
+    );
+    assert_eq!(
+        deserialize(Some(Version::new(0, 24, 0)), src),
+        Err(Error::Custom(
+            "missing field `lastloadedplaylist`, which is sent since protocol version 0.24.0"
+                .to_string()
+        ))
+    );
+
+    let src = b"state: stop\nOK\n";
+    assert_eq!(
+        deserialize(Some(Version::new(0, 19, 0)), src),
+        Ok(Status {
+            state: "stop".to_string(),
+            elapsed: None,
+            last_loaded_playlist: None,
+        })
+    );
+    assert!(deserialize(Some(Version::new(0, 20, 0)), src).is_err());
+}
+
+#[test]
+fn serialize() {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    Status {
+        state: "play".to_string(),
+        elapsed: Some(1.5),
+        last_loaded_playlist: None,
+    }
+    .serialize(&mut ser)
+    .unwrap();
+    assert_eq!(ser.into_inner(), b"state: play\nelapsed: 1.5\nOK\n");
+}
+
+#[test]
+fn serialize_map_keys_verbatim() {
+    let mut ser = ResponseSerializer::from_write(Vec::new());
+    BTreeMap::from([("x@1.2", "a"), ("y", "b")])
+        .serialize(&mut ser)
+        .unwrap();
+    assert_eq!(ser.into_inner(), b"x@1.2: a\ny: b\nOK\n");
+}